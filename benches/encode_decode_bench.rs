@@ -1,6 +1,7 @@
-use criterion::{black_box, criterion_group, criterion_main, Criterion};
-use json2toon_rs::{decode, encode, DecoderOptions, EncoderOptions};
+use criterion::{criterion_group, criterion_main, Criterion};
+use json2toon_rs::{decode, encode, encode_cow, DecoderOptions, EncoderOptions};
 use serde_json::json;
+use std::hint::black_box;
 
 fn get_complex_json() -> serde_json::Value {
     json!({
@@ -99,5 +100,52 @@ fn benchmark_decode(c: &mut Criterion) {
     });
 }
 
-criterion_group!(benches, benchmark_encode, benchmark_decode);
+fn benchmark_encode_scalars(c: &mut Criterion) {
+    let values = [
+        json!(true),
+        json!(null),
+        json!("plain"),
+        json!("needs: quoting"),
+        json!(42),
+    ];
+    let options = EncoderOptions::default();
+
+    c.bench_function("encode_scalars", |b| {
+        b.iter(|| {
+            for value in &values {
+                black_box(encode(black_box(value), black_box(&options)));
+            }
+        })
+    });
+    c.bench_function("encode_cow_scalars", |b| {
+        b.iter(|| {
+            for value in &values {
+                black_box(encode_cow(black_box(value), black_box(&options)));
+            }
+        })
+    });
+}
+
+fn benchmark_decode_long_line(c: &mut Criterion) {
+    let decode_options = DecoderOptions::default();
+    let mut group = c.benchmark_group("decode_long_key_value_line");
+
+    // Decode time should grow linearly with the line length
+    for len in [1_000, 10_000, 100_000] {
+        let key = "k\\\"".repeat(len / 3);
+        let toon_string = format!("\"{}\": {}", key, "v".repeat(len));
+        group.bench_function(len.to_string(), |b| {
+            b.iter(|| decode(black_box(&toon_string), black_box(&decode_options)).unwrap())
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(
+    benches,
+    benchmark_encode,
+    benchmark_decode,
+    benchmark_encode_scalars,
+    benchmark_decode_long_line
+);
 criterion_main!(benches);
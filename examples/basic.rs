@@ -1,4 +1,4 @@
-use json2toon_rs::{encode, Delimiter, EncoderOptions};
+use json2toon_rs::{encode, Delimiter, EncoderOptions, IndentStyle};
 use serde_json::json;
 
 fn main() {
@@ -70,8 +70,9 @@ fn main() {
         ]
     });
     let options = EncoderOptions {
-        indent: 2,
+        indent: IndentStyle::Spaces(2),
         delimiter: Delimiter::Tab,
+        ..Default::default()
     };
     println!("{}\n", encode(&data, &options));
 
@@ -81,8 +82,9 @@ fn main() {
         "categories": ["reading", "gaming", "coding"]
     });
     let options = EncoderOptions {
-        indent: 2,
+        indent: IndentStyle::Spaces(2),
         delimiter: Delimiter::Pipe,
+        ..Default::default()
     };
     println!("{}\n", encode(&data, &options));
 
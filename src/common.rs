@@ -6,15 +6,130 @@ pub enum Delimiter {
     Comma,
     Tab,
     Pipe,
+    Semicolon,
+}
+
+/// Indentation unit used for nesting levels
+///
+/// With [`IndentStyle::Tabs`], each nesting level is one leading tab. This
+/// combines safely with [`Delimiter::Tab`]: indentation is only ever read from
+/// the start of a line, and the encoder always quotes empty strings, so a
+/// tabular row never begins with a bare tab delimiter. Hand-written rows whose
+/// first cell is left empty (a row starting with a delimiter tab) are
+/// indistinguishable from extra indentation and must quote that cell as `""`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum IndentStyle {
+    /// The given number of spaces per level
+    Spaces(usize),
+    /// One tab per level
+    Tabs,
+}
+
+impl Default for IndentStyle {
+    fn default() -> Self {
+        IndentStyle::Spaces(2)
+    }
+}
+
+/// TOON specification version that encoding and decoding follow
+///
+/// Only v2.0 exists today. Format decisions that a later revision may change
+/// match on this, so new versions can be added without altering the default.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[non_exhaustive]
+pub enum SpecVersion {
+    /// TOON v2.0
+    #[default]
+    V2_0,
+}
+
+impl SpecVersion {
+    /// The version number as written in the specification, e.g. `"2.0"`
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            SpecVersion::V2_0 => "2.0",
+        }
+    }
+}
+
+/// Check if a string is a plain decimal integer literal (`-?[0-9]+`)
+pub(crate) fn is_integer_literal(s: &str) -> bool {
+    let digits = s.strip_prefix('-').unwrap_or(s);
+    !digits.is_empty() && digits.bytes().all(|b| b.is_ascii_digit())
+}
+
+/// Check if a string matches the TOON number grammar
+///
+/// The grammar is `-?[0-9]+(\.[0-9]+)?([eE][+-]?[0-9]+)?`. Unlike `f64::from_str`
+/// it rejects `inf`, `NaN`, a leading `+`, bare `.5` / `5.` forms and digit
+/// separators, so the result never depends on Rust's float parser quirks.
+/// Leading zeros are accepted here; see [`has_leading_zeros`].
+pub(crate) fn is_numeric_literal(s: &str) -> bool {
+    fn digits(b: &[u8]) -> usize {
+        b.iter().take_while(|c| c.is_ascii_digit()).count()
+    }
+
+    let b = s.as_bytes();
+    let mut i = usize::from(b.first() == Some(&b'-'));
+
+    let int_len = digits(&b[i..]);
+    if int_len == 0 {
+        return false;
+    }
+    i += int_len;
+
+    if b.get(i) == Some(&b'.') {
+        let frac_len = digits(&b[i + 1..]);
+        if frac_len == 0 {
+            return false;
+        }
+        i += 1 + frac_len;
+    }
+
+    if matches!(b.get(i), Some(b'e' | b'E')) {
+        i += 1;
+        if matches!(b.get(i), Some(b'+' | b'-')) {
+            i += 1;
+        }
+        let exp_len = digits(&b[i..]);
+        if exp_len == 0 {
+            return false;
+        }
+        i += exp_len;
+    }
+
+    i == b.len()
+}
+
+/// Check if a numeric literal has a forbidden leading zero (e.g. `05`, `-007`)
+pub(crate) fn has_leading_zeros(s: &str) -> bool {
+    let digits = s.strip_prefix('-').unwrap_or(s).as_bytes();
+    digits.len() > 1 && digits[0] == b'0' && digits[1].is_ascii_digit()
 }
 
 impl Delimiter {
+    /// Every delimiter variant, comma first
+    pub fn all() -> &'static [Delimiter] {
+        &[
+            Delimiter::Comma,
+            Delimiter::Tab,
+            Delimiter::Pipe,
+            Delimiter::Semicolon,
+        ]
+    }
+
+    /// Returns the delimiter whose character is `c`, if any
+    pub fn from_char(c: char) -> Option<Delimiter> {
+        Self::all().iter().copied().find(|d| d.as_char() == c)
+    }
+
     /// Returns the character representation
-    pub(crate) fn as_char(&self) -> char {
+    pub fn as_char(&self) -> char {
         match self {
             Delimiter::Comma => ',',
             Delimiter::Tab => '\t',
             Delimiter::Pipe => '|',
+            Delimiter::Semicolon => ';',
         }
     }
 
@@ -24,6 +139,21 @@ impl Delimiter {
             Delimiter::Comma => "",
             Delimiter::Tab => "\t",
             Delimiter::Pipe => "|",
+            Delimiter::Semicolon => ";",
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_delimiter_from_char_round_trip() {
+        assert_eq!(Delimiter::all().len(), 4);
+        for &d in Delimiter::all() {
+            assert_eq!(Delimiter::from_char(d.as_char()), Some(d));
         }
+        assert_eq!(Delimiter::from_char(':'), None);
     }
 }
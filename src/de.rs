@@ -0,0 +1,96 @@
+//! Deserialization of TOON directly into Rust types via serde
+
+use crate::decoder::{decode, DecoderOptions};
+use crate::error::DecodeError;
+use alloc::string::ToString;
+use serde::de::DeserializeOwned;
+
+/// Decode a TOON document into any type implementing `serde::Deserialize`
+///
+/// The input is first decoded to a `serde_json::Value` and then converted with
+/// `serde_json::from_value`, so the usual serde data model applies: objects map
+/// to structs or maps, and arrays of any TOON form (inline, tabular, or list)
+/// map to `Vec<T>`. A tabular array such as `users[2]{id,name}:` deserializes
+/// naturally into a `Vec<User>` field.
+///
+/// Errors from the conversion step are reported as [`DecodeError::Deserialize`].
+///
+/// ```rust
+/// use json2toon_rs::{from_str, DecoderOptions};
+/// use serde::Deserialize;
+///
+/// #[derive(Deserialize)]
+/// struct Person {
+///     name: String,
+///     age: u32,
+/// }
+///
+/// let person: Person = from_str("name: Alice\nage: 30", &DecoderOptions::default()).unwrap();
+/// assert_eq!(person.name, "Alice");
+/// assert_eq!(person.age, 30);
+/// ```
+pub fn from_str<T: DeserializeOwned>(
+    input: &str,
+    options: &DecoderOptions,
+) -> Result<T, DecodeError> {
+    let value = decode(input, options)?;
+    serde_json::from_value(value).map_err(|e| DecodeError::Deserialize(e.to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde::Deserialize;
+
+    #[derive(Debug, Deserialize, PartialEq)]
+    struct User {
+        id: u32,
+        name: String,
+        active: bool,
+    }
+
+    #[derive(Debug, Deserialize, PartialEq)]
+    struct Team {
+        name: String,
+        users: Vec<User>,
+    }
+
+    #[test]
+    fn test_from_str_struct() {
+        let toon = "name: Core\nusers[2]{id,name,active}:\n  1,Alice,true\n  2,Bob,false";
+        let team: Team = from_str(toon, &DecoderOptions::default()).unwrap();
+        assert_eq!(
+            team,
+            Team {
+                name: "Core".to_string(),
+                users: vec![
+                    User {
+                        id: 1,
+                        name: "Alice".to_string(),
+                        active: true
+                    },
+                    User {
+                        id: 2,
+                        name: "Bob".to_string(),
+                        active: false
+                    },
+                ],
+            }
+        );
+    }
+
+    #[test]
+    fn test_from_str_type_mismatch() {
+        let result: Result<User, _> = from_str("id: abc", &DecoderOptions::default());
+        assert!(matches!(result, Err(DecodeError::Deserialize(_))));
+    }
+
+    #[test]
+    fn test_from_str_propagates_decode_errors() {
+        let result: Result<Vec<String>, _> = from_str("[3]:\n  - a", &DecoderOptions::default());
+        assert!(matches!(
+            result,
+            Err(DecodeError::ArrayLengthMismatch { .. })
+        ));
+    }
+}
@@ -1,24 +1,207 @@
 //! TOON to JSON decoder implementation
 
-use crate::common::Delimiter;
-use crate::error::DecodeError;
+use crate::common::{
+    has_leading_zeros, is_integer_literal, is_numeric_literal, Delimiter, IndentStyle, SpecVersion,
+};
+use crate::error::{DecodeError, ErrorSpan, Warning};
+use alloc::borrow::Cow;
+use alloc::collections::BTreeMap;
+use alloc::format;
+use alloc::string::{String, ToString};
+use alloc::vec;
+use alloc::vec::Vec;
+use core::cell::RefCell;
+use core::fmt::Write as _;
 use serde_json::Value;
-use std::borrow::Cow;
+#[cfg(feature = "std")]
+use std::io::{BufRead, BufReader, Read};
 
 /// Decoder configuration options
 #[derive(Debug, Clone)]
 pub struct DecoderOptions {
-    /// Spaces per indentation level (default: 2)
-    pub indent: usize,
+    /// Indentation per nesting level (default: 2 spaces)
+    ///
+    /// With `strict` off and space indentation, the width is instead inferred
+    /// from the first indented line of the input.
+    pub indent: IndentStyle,
     /// Strict mode - enforces counts, indentation, etc. (default: true)
+    ///
+    /// Without it, a tabular row with more cells than fields keeps only the
+    /// first cells, one per field. Nothing is dropped silently: every such row
+    /// is reported by [`decode_with_warnings`] as a
+    /// [`Warning::RowWidthMismatch`], as are array length mismatches.
     pub strict: bool,
+    /// Skip full-line `#` comments (default: false)
+    ///
+    /// Only lines whose first non-whitespace character is `#` are treated as
+    /// comments; a `#` anywhere else, including inside values, is kept. Note
+    /// that an unquoted tabular row starting with `#` is also skipped when
+    /// this is enabled.
+    pub allow_comments: bool,
+    /// Accept tabular rows with fewer cells than fields (default: false)
+    ///
+    /// Missing trailing cells become `null`. Rows with more cells than fields
+    /// are still rejected in strict mode, since the extra cells would be lost;
+    /// without `strict` the extra cells are dropped with a warning (see
+    /// `strict`).
+    pub ragged_rows: bool,
+    /// Decode empty tabular cells as `null` (default: false)
+    ///
+    /// An unquoted empty cell, as in the row `1,,true`, is otherwise an empty
+    /// string. A quoted `""` cell is always an empty string.
+    pub empty_cell_as_null: bool,
+    /// TOON specification version to accept (default: `SpecVersion::V2_0`)
+    pub spec_version: SpecVersion,
+    /// Expected types of tabular fields, by field name (default: None)
+    ///
+    /// Cells of a listed field are coerced to its type instead of being
+    /// inferred, so `"123"` can become a number or `123` stay a string. A
+    /// cell matching `null_tokens` stays `null` whatever the type. A cell that cannot
+    /// be coerced is an error in strict mode; otherwise it is inferred as
+    /// usual. Unlisted fields, and all fields when this is `None`, are inferred.
+    pub field_types: Option<BTreeMap<String, ValueType>>,
+    /// Unquoted spellings decoded as `null` (default: `["null"]`)
+    ///
+    /// Replaces the default set, so include `"null"` to keep it alongside
+    /// extra spellings such as `~`. Quoted values are always strings.
+    pub null_tokens: Vec<String>,
+    /// Retry mismatched tabular rows with other delimiters (default: false)
+    ///
+    /// A heuristic for exports whose rows disagree with their header, such as
+    /// tab-separated rows under a header declaring the default comma. When a
+    /// row's cell count differs from the field count, it is split again with
+    /// comma, tab, pipe and semicolon in turn, and the first split that gives
+    /// exactly one cell per field is used. Rows that no delimiter fits are
+    /// checked as usual.
+    pub sniff_delimiter: bool,
+    /// Accept `0x`, `0o` and `0b` integers and `_` digit separators (default: false)
+    ///
+    /// With this set, unquoted `0xFF`, `-0b101` and `1_000_000` decode as
+    /// numbers. A separator must sit between two digits, so `_1` and `1__0`
+    /// stay strings. Per the spec these forms are strings, and the encoder
+    /// leaves such strings unquoted, so enable this only for input known to
+    /// use them as numbers.
+    pub extended_number_literals: bool,
+    /// Split an unquoted field value containing commas into an array (default: false)
+    ///
+    /// A lenient convenience for hand-written input such as `tags: a,b,c`,
+    /// which then decodes as `["a", "b", "c"]` instead of the string
+    /// `"a,b,c"`. The comma is the document delimiter, so it applies whatever
+    /// delimiter arrays elsewhere declare. Quoted values and commas inside
+    /// quotes are left alone, but an encoder using a tab or pipe delimiter
+    /// writes object values with commas unquoted, so enable this only for
+    /// input that never relies on that.
+    pub infer_inline_arrays: bool,
+    /// Ignore one trailing delimiter in an inline array (default: false)
+    ///
+    /// With this set, `tags[3]: a,b,c,` decodes as three values instead of
+    /// four with an empty last one. Only an unquoted empty last value is
+    /// dropped, so `a,b,""` keeps its empty string.
+    pub allow_trailing_delimiter: bool,
+    /// How to handle unknown escape sequences in quoted strings (default: None)
+    ///
+    /// `None` follows `strict`: [`EscapePolicy::Strict`] when it is set and
+    /// [`EscapePolicy::Preserve`] otherwise.
+    pub escape_policy: Option<EscapePolicy>,
+    /// Remove the indentation shared by every line before decoding (default: false)
+    ///
+    /// Lets a fragment copied from inside a larger document, such as
+    /// `  a: 1\n  b: 2`, decode as if it started at column zero. The
+    /// indentation removed is that of the first content line, which in a
+    /// well-formed fragment is the smallest; blank and comment lines are
+    /// skipped when finding it. Lines that do not start with it are left as
+    /// they are.
+    pub auto_dedent: bool,
+    /// Read a list item that starts on its array header line (default: false)
+    ///
+    /// Decodes `items[1]: - a: 1`, as written by
+    /// `EncoderOptions::array_on_same_line`, like the item on its own line.
+    /// This is an extension to TOON; when off, `- a: 1` is an inline value.
+    pub same_line_list_items: bool,
+    /// Read `{key: value, ...}` field values as objects (default: false)
+    ///
+    /// Decodes `point: {x: 1, y: 2}`, as written by
+    /// `EncoderOptions::inline_small_objects`. This is an extension to TOON;
+    /// when off, braced text is a string.
+    pub inline_objects: bool,
+}
+
+/// Target type for a tabular field in [`DecoderOptions::field_types`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ValueType {
+    /// Keep the cell text as a string, even if it looks like a number or bool
+    String,
+    /// A number in TOON number syntax, quoted or not
+    Number,
+    /// `true` or `false`, quoted or not
+    Bool,
+}
+
+/// Handling of unknown escape sequences, for [`DecoderOptions::escape_policy`]
+///
+/// Applies to escapes outside the spec's `\\`, `\"`, `\n`, `\r`, `\t` and
+/// `\uXXXX`, such as `\q`, a malformed `\u` or a trailing lone backslash.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EscapePolicy {
+    /// Reject the string with [`DecodeError::InvalidEscapeSequence`]
+    Strict,
+    /// Keep the sequence verbatim: `"a\qb"` decodes as `a\qb`
+    Preserve,
+    /// Drop the backslash and keep the character: `"a\qb"` decodes as `aqb`
+    Unescape,
+}
+
+impl DecoderOptions {
+    /// Strict preset, identical to `DecoderOptions::default()`
+    ///
+    /// Enforces declared array lengths and tabular row widths, requires
+    /// indentation to be an exact multiple of `indent`, and rejects unknown
+    /// escape sequences.
+    pub fn strict() -> Self {
+        Self::default()
+    }
+
+    /// Lenient preset for hand-written or slightly malformed input
+    ///
+    /// Compared to [`DecoderOptions::strict`], this turns off:
+    /// - array length checks: arrays keep however many items are present
+    /// - tabular row width checks: short rows are padded with `null` (see
+    ///   `ragged_rows`), extra cells are ignored
+    /// - indentation validation: the space indent unit is inferred from the
+    ///   first indented line, and depth is the leading indentation divided by
+    ///   that unit, rounded down
+    /// - escape validation: unknown escapes such as `\q` are kept verbatim
+    ///
+    /// It also sets `allow_trailing_delimiter`, so `tags[2]: a,b,` is two values.
+    pub fn lenient() -> Self {
+        Self {
+            strict: false,
+            ragged_rows: true,
+            allow_trailing_delimiter: true,
+            ..Self::default()
+        }
+    }
 }
 
 impl Default for DecoderOptions {
     fn default() -> Self {
         Self {
-            indent: 2,
+            indent: IndentStyle::default(),
             strict: true,
+            allow_comments: false,
+            ragged_rows: false,
+            empty_cell_as_null: false,
+            spec_version: SpecVersion::default(),
+            field_types: None,
+            null_tokens: vec!["null".to_string()],
+            sniff_delimiter: false,
+            extended_number_literals: false,
+            infer_inline_arrays: false,
+            allow_trailing_delimiter: false,
+            escape_policy: None,
+            auto_dedent: false,
+            same_line_list_items: false,
+            inline_objects: false,
         }
     }
 }
@@ -29,10 +212,537 @@ pub fn decode(input: &str, options: &DecoderOptions) -> Result<Value, DecodeErro
     decoder.decode()
 }
 
+/// Decode TOON format to JSON value, recording the form of every array
+///
+/// The returned [`DecodeTrace`] maps the JSON pointer of each array to the
+/// [`ArrayForm`] it was written in. [`decode`] skips this bookkeeping entirely.
+///
+/// ```rust
+/// use json2toon_rs::{decode_with_trace, ArrayForm, DecoderOptions};
+///
+/// let input = "tags[2]: a,b\nusers[1]{id}:\n  1";
+/// let (_, trace) = decode_with_trace(input, &DecoderOptions::default()).unwrap();
+/// assert_eq!(trace.get("/tags"), Some(ArrayForm::Inline));
+/// assert_eq!(trace.get("/users"), Some(ArrayForm::Tabular));
+/// ```
+pub fn decode_with_trace(
+    input: &str,
+    options: &DecoderOptions,
+) -> Result<(Value, DecodeTrace), DecodeError> {
+    let mut decoder = Decoder::new(input, options)?;
+    decoder.trace = Some(Tracer::default());
+    let value = decoder.decode()?;
+    let trace = decoder.trace.take().unwrap_or_default().trace;
+    Ok((value, trace))
+}
+
+/// Decode TOON format to JSON value, also returning count mismatches that
+/// were tolerated
+///
+/// With `strict` off, array lengths and tabular row widths are not enforced,
+/// so a truncated array decodes without error. Each such mismatch is returned
+/// as a [`Warning`]; short rows accepted through `ragged_rows` are reported
+/// too. In strict mode the mismatches are errors, so the warnings cover only
+/// `ragged_rows`.
+///
+/// ```rust
+/// use json2toon_rs::{decode_with_warnings, DecoderOptions, Warning};
+///
+/// let (value, warnings) =
+///     decode_with_warnings("tags[3]: a,b", &DecoderOptions::lenient()).unwrap();
+/// assert_eq!(value["tags"].as_array().unwrap().len(), 2);
+/// assert_eq!(
+///     warnings,
+///     vec![Warning::ArrayLengthMismatch { line: 1, expected: 3, found: 2 }]
+/// );
+/// ```
+pub fn decode_with_warnings(
+    input: &str,
+    options: &DecoderOptions,
+) -> Result<(Value, Vec<Warning>), DecodeError> {
+    let mut decoder = Decoder::new(input, options)?;
+    decoder.warnings = Some(RefCell::new(Vec::new()));
+    let value = decoder.decode()?;
+    let warnings = decoder.warnings.take().unwrap_or_default().into_inner();
+    Ok((value, warnings))
+}
+
+/// Parse a single `key: value` line, such as `age: 30`, into its key and value
+///
+/// Surrounding whitespace is ignored. The value must be a primitive: a line
+/// opening a nested object (`user:`) or an array (`tags[2]: a,b`), or input
+/// spanning several lines, is a [`DecodeError::ParseError`]. A line without a
+/// key, including a list item, is a [`DecodeError::InvalidLine`].
+///
+/// ```rust
+/// use json2toon_rs::{parse_assignment, DecoderOptions};
+/// use serde_json::json;
+///
+/// let (key, value) = parse_assignment("age: 30", &DecoderOptions::default()).unwrap();
+/// assert_eq!((key.as_str(), value), ("age", json!(30)));
+/// assert!(parse_assignment("user:", &DecoderOptions::default()).is_err());
+/// ```
+pub fn parse_assignment(
+    line: &str,
+    options: &DecoderOptions,
+) -> Result<(String, Value), DecodeError> {
+    let decoder = Decoder::new(line.trim(), options)?;
+    let [parsed] = decoder.lines.as_slice() else {
+        return Err(DecodeError::ParseError {
+            line: 1,
+            message: "expected a single key-value line".to_string(),
+        });
+    };
+    let (content, line_num) = (parsed.content.as_str(), parsed.line_num);
+
+    let is_list_item = content.starts_with("- ") || content == "-";
+    let Some(colon_pos) = Decoder::find_unquoted(content, ':').filter(|_| !is_list_item) else {
+        return Err(DecodeError::InvalidLine {
+            line: line_num,
+            content: content.to_string(),
+        });
+    };
+    let raw_key = &content[..colon_pos];
+    let has_header = Decoder::array_key_bracket(raw_key).is_some();
+    if has_header || content[colon_pos + 1..].trim().is_empty() {
+        return Err(DecodeError::ParseError {
+            line: line_num,
+            message: "value must be a primitive, not an object or array".to_string(),
+        });
+    }
+
+    let (key, value) = decoder
+        .parse_key_value(content, line_num)?
+        .expect("line has an unquoted colon");
+    let value = decoder.parse_primitive(&value, line_num)?;
+    Ok((key, value))
+}
+
+/// Decode a series of TOON documents separated by `---` lines
+///
+/// A separator is a line consisting of exactly `---`, following the YAML
+/// convention. Each document is decoded independently, and segments with no
+/// content (such as before a leading separator) are skipped. Error line
+/// numbers refer to the whole input.
+///
+/// ```rust
+/// use json2toon_rs::{decode_many, DecoderOptions};
+/// use serde_json::json;
+///
+/// let docs = decode_many("id: 1\n---\nid: 2", &DecoderOptions::default()).unwrap();
+/// assert_eq!(docs, vec![json!({"id": 1}), json!({"id": 2})]);
+/// ```
+pub fn decode_many(input: &str, options: &DecoderOptions) -> Result<Vec<Value>, DecodeError> {
+    // (start, end, first line) of each segment; a segment begins right after
+    // its separator, so its first line is the separator's (now empty) line
+    let mut segments = Vec::new();
+    let (mut start, mut first_line) = (0, 1);
+    for (i, line) in Decoder::split_lines(input).enumerate() {
+        if line == "---" {
+            let offset = line.as_ptr() as usize - input.as_ptr() as usize;
+            segments.push((start, offset, first_line));
+            start = offset + line.len();
+            first_line = i + 1;
+        }
+    }
+    segments.push((start, input.len(), first_line));
+
+    let mut documents = Vec::new();
+    for (start, end, first_line) in segments {
+        let (lines, errors) =
+            Decoder::parse_segment(input, &input[start..end], first_line, options);
+        if let Some(err) = errors.into_iter().next() {
+            return Err(err);
+        }
+        if lines.is_empty() {
+            continue;
+        }
+
+        let mut decoder = Decoder {
+            lines,
+            options,
+            pos: 0,
+            collected: None,
+            trace: None,
+            warnings: None,
+            pending_item: None,
+        };
+        documents.push(decoder.decode()?);
+    }
+    Ok(documents)
+}
+
+/// Decode TOON read from `reader` to JSON value
+///
+/// The input is consumed line by line through a [`StreamingDecoder`], so it is
+/// never held as one contiguous string. Read failures and invalid UTF-8 are
+/// reported as [`DecodeError::Io`]. The reader is buffered internally.
+#[cfg(feature = "std")]
+pub fn decode_reader<R: Read>(
+    reader: &mut R,
+    options: &DecoderOptions,
+) -> Result<Value, DecodeError> {
+    let mut reader = BufReader::new(reader);
+    let mut decoder = StreamingDecoder::new(options.clone());
+    let mut line = String::new();
+
+    loop {
+        line.clear();
+        let read = reader
+            .read_line(&mut line)
+            .map_err(|e| DecodeError::Io(e.to_string()))?;
+        if read == 0 {
+            break;
+        }
+        decoder.push_str(&line);
+    }
+
+    decoder.finish()
+}
+
+/// Decode TOON format to JSON value, locating the error in the input on failure
+///
+/// On error the [`ErrorSpan`] gives the byte range of the offending text
+/// (an invalid escape, a duplicate key, an array header, or otherwise the
+/// whole line), suitable for editor diagnostics. The span is `None` when the
+/// error cannot be tied to a line of the input.
+///
+/// ```rust
+/// use json2toon_rs::{decode_with_span, DecoderOptions};
+///
+/// let input = "a: 1\nb: \"x\\qy\"";
+/// let (_, span) = decode_with_span(input, &DecoderOptions::default()).unwrap_err();
+/// let span = span.unwrap();
+/// assert_eq!(&input[span.start..span.end], "\\q");
+/// ```
+pub fn decode_with_span(
+    input: &str,
+    options: &DecoderOptions,
+) -> Result<Value, (DecodeError, Option<ErrorSpan>)> {
+    let (lines, errors) = Decoder::parse_lines(input, options);
+    let mut decoder = Decoder {
+        lines,
+        options,
+        pos: 0,
+        collected: None,
+        trace: None,
+        warnings: None,
+        pending_item: None,
+    };
+
+    let result = match errors.into_iter().next() {
+        Some(err) => Err(err),
+        None => decoder.decode(),
+    };
+    result.map_err(|err| {
+        let span = decoder.error_span(&err);
+        (err, span)
+    })
+}
+
+/// Incremental decoder for TOON input that arrives in chunks
+///
+/// Chunks may split the input anywhere, including in the middle of a line;
+/// incomplete trailing text is buffered until a later chunk (or [`finish`])
+/// completes it. Each complete line is parsed and validated as soon as it is
+/// available, while the document structure is resolved once all input is in.
+///
+/// ```rust
+/// use json2toon_rs::{DecoderOptions, StreamingDecoder};
+/// use serde_json::json;
+///
+/// let mut decoder = StreamingDecoder::new(DecoderOptions::default());
+/// decoder.push_str("name: Al");
+/// decoder.push_str("ice\nage: 30\n");
+/// assert_eq!(decoder.finish().unwrap(), json!({"name": "Alice", "age": 30}));
+/// ```
+///
+/// [`finish`]: StreamingDecoder::finish
+#[derive(Debug)]
+pub struct StreamingDecoder {
+    options: DecoderOptions,
+    lines: Vec<Line>,
+    partial: String,
+    line_count: usize,
+    byte_count: usize,
+    indent_inferred: bool,
+    /// Indentation stripped from every line under `auto_dedent`, once known
+    dedent: Option<String>,
+    error: Option<DecodeError>,
+}
+
+impl StreamingDecoder {
+    /// Create a streaming decoder with the given options
+    pub fn new(options: DecoderOptions) -> Self {
+        Self {
+            options,
+            lines: Vec::new(),
+            partial: String::new(),
+            line_count: 0,
+            byte_count: 0,
+            indent_inferred: false,
+            dedent: None,
+            error: None,
+        }
+    }
+
+    /// Feed the next chunk of input
+    ///
+    /// Errors found while parsing complete lines are reported by `finish`.
+    pub fn push_str(&mut self, chunk: &str) {
+        self.partial.push_str(chunk);
+
+        while let Some(end) = self.partial.find(['\n', '\r']) {
+            let bytes = self.partial.as_bytes();
+            let terminator = match bytes[end] {
+                // A trailing `\r` may be the first half of a `\r\n` split across chunks
+                b'\r' if end + 1 == bytes.len() => break,
+                b'\r' if bytes[end + 1] == b'\n' => 2,
+                _ => 1,
+            };
+            let rest = self.partial.split_off(end + terminator);
+            let mut line = core::mem::replace(&mut self.partial, rest);
+            line.truncate(end);
+            self.process_line(&line, terminator);
+        }
+    }
+
+    /// Flush any buffered partial line and decode the accumulated document
+    pub fn finish(mut self) -> Result<Value, DecodeError> {
+        if !self.partial.is_empty() {
+            let line = core::mem::take(&mut self.partial);
+            match line.strip_suffix('\r') {
+                Some(stripped) => self.process_line(stripped, 1),
+                None => self.process_line(&line, 0),
+            }
+        }
+
+        if let Some(err) = self.error {
+            return Err(err);
+        }
+
+        let mut decoder = Decoder {
+            lines: self.lines,
+            options: &self.options,
+            pos: 0,
+            collected: None,
+            trace: None,
+            warnings: None,
+            pending_item: None,
+        };
+        decoder.decode()
+    }
+
+    /// Parse one complete line, given without its terminator of `terminator_len` bytes
+    fn process_line(&mut self, line: &str, terminator_len: usize) {
+        let mut offset = self.byte_count;
+        self.line_count += 1;
+        self.byte_count += line.len() + terminator_len;
+        let mut line = match line.strip_prefix('\u{FEFF}') {
+            Some(stripped) if self.line_count == 1 => {
+                offset += '\u{FEFF}'.len_utf8();
+                stripped
+            }
+            _ => line,
+        };
+        if self.error.is_some() {
+            return;
+        }
+
+        if self.options.auto_dedent {
+            if self.dedent.is_none() {
+                self.dedent = Decoder::dedent_prefix(line, &self.options).map(str::to_string);
+            }
+            if let Some(stripped) = self.dedent.as_deref().and_then(|p| line.strip_prefix(p)) {
+                offset += line.len() - stripped.len();
+                line = stripped;
+            }
+        }
+
+        if !self.indent_inferred {
+            if let Some(width) = Decoder::inferred_indent(line, &self.options) {
+                self.options.indent = IndentStyle::Spaces(width);
+                self.indent_inferred = true;
+            }
+        }
+
+        let mut errors = Vec::new();
+        if let Some(parsed) =
+            Decoder::parse_line(line, self.line_count, offset, &self.options, &mut errors)
+        {
+            self.lines.push(parsed);
+        }
+        self.error = errors.into_iter().next();
+    }
+}
+
+/// Check a TOON document for errors without keeping the decoded value
+///
+/// Unlike [`decode`], which stops at the first problem, this keeps going past
+/// recoverable errors (indentation, array lengths, row widths, escapes,
+/// duplicate keys) and returns all of them, ordered by line. An error that
+/// leaves the structure unrecoverable (such as a malformed array header) ends
+/// the scan and is reported last. Valid input yields an empty vector.
+pub fn validate(input: &str, options: &DecoderOptions) -> Vec<DecodeError> {
+    decode_collecting(input, options).1
+}
+
+/// Decode TOON format to JSON value, reporting every recoverable error at once
+///
+/// Where [`decode`] stops at the first array length or row width mismatch,
+/// this keeps decoding with a best-effort value and gathers all of them, along
+/// with the other recoverable errors that [`validate`] reports. A single error
+/// is returned as is; several are returned together, ordered by line, as
+/// [`DecodeError::Multiple`].
+///
+/// ```rust
+/// use json2toon_rs::{decode_checked, DecodeError, DecoderOptions};
+///
+/// let input = "a[3]: 1,2\nb[1]: 1,2";
+/// let Err(DecodeError::Multiple(errors)) = decode_checked(input, &DecoderOptions::default())
+/// else {
+///     panic!("expected both mismatches");
+/// };
+/// assert_eq!(errors.len(), 2);
+/// ```
+pub fn decode_checked(input: &str, options: &DecoderOptions) -> Result<Value, DecodeError> {
+    let (value, mut errors) = decode_collecting(input, options);
+    match (value, errors.len()) {
+        (Some(value), 0) => Ok(value),
+        (_, 1) => Err(errors.remove(0)),
+        _ => Err(DecodeError::Multiple(errors)),
+    }
+}
+
+/// Number of indented lines [`detect_indent`] looks at
+const DETECT_INDENT_SAMPLE: usize = 32;
+
+/// Guess the space indentation width of a TOON document
+///
+/// Scans the first indented lines and returns the greatest common divisor of
+/// their leading space counts, so a file indented by 4 spaces gives `Some(4)`,
+/// ready for [`IndentStyle::Spaces`]. Returns `None` when no line is indented
+/// or when indentation uses tabs, which [`IndentStyle::Tabs`] covers. Blank
+/// lines are skipped.
+///
+/// ```rust
+/// use json2toon_rs::{decode, detect_indent, DecoderOptions, IndentStyle};
+///
+/// let input = "a:\n    b:\n        c: 1";
+/// let width = detect_indent(input).unwrap();
+/// assert_eq!(width, 4);
+///
+/// let options = DecoderOptions { indent: IndentStyle::Spaces(width), ..Default::default() };
+/// assert!(decode(input, &options).is_ok());
+/// ```
+pub fn detect_indent(input: &str) -> Option<usize> {
+    let mut width = 0;
+    for line in input
+        .lines()
+        .filter(|line| !line.trim().is_empty() && Decoder::indent_len(line) > 0)
+        .take(DETECT_INDENT_SAMPLE)
+    {
+        let leading = Decoder::indent_len(line);
+        if line[..leading].contains('\t') {
+            return None;
+        }
+        width = gcd(width, leading);
+    }
+    (width > 0).then_some(width)
+}
+
+fn gcd(a: usize, b: usize) -> usize {
+    if b == 0 {
+        a
+    } else {
+        gcd(b, a % b)
+    }
+}
+
+/// Decode while collecting recoverable errors, ordered by line
+///
+/// An unrecoverable error leaves no value and is placed last.
+fn decode_collecting(input: &str, options: &DecoderOptions) -> (Option<Value>, Vec<DecodeError>) {
+    let (lines, mut errors) = Decoder::parse_lines(input, options);
+    let mut decoder = Decoder {
+        lines,
+        options,
+        pos: 0,
+        collected: Some(RefCell::new(Vec::new())),
+        trace: None,
+        warnings: None,
+        pending_item: None,
+    };
+
+    let result = decoder.decode();
+    if let Some(collected) = decoder.collected.take() {
+        errors.extend(collected.into_inner());
+    }
+    errors.sort_by_key(|e| e.line());
+    match result {
+        Ok(value) => (Some(value), errors),
+        Err(err) => {
+            errors.push(err);
+            (None, errors)
+        }
+    }
+}
+
 struct Decoder<'a> {
     lines: Vec<Line>,
     options: &'a DecoderOptions,
     pos: usize,
+    /// Sink for recoverable errors when validating; `None` fails fast
+    collected: Option<RefCell<Vec<DecodeError>>>,
+    /// Array form recorder for `decode_with_trace`; `None` skips path tracking
+    trace: Option<Tracer>,
+    /// Sink for unenforced count mismatches in `decode_with_warnings`
+    warnings: Option<RefCell<Vec<Warning>>>,
+    /// List item read from its array header line (`same_line_list_items`),
+    /// taken as the first item by `decode_list_array`
+    pending_item: Option<Line>,
+}
+
+/// The TOON form an array was decoded from
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ArrayForm {
+    /// Primitive values on the header line: `tags[2]: a,b`
+    Inline,
+    /// A field header followed by delimited rows: `users[2]{id,name}:`
+    Tabular,
+    /// One `- ` item per line (also used for empty arrays such as `items[0]:`)
+    List,
+}
+
+/// The array forms seen while decoding, keyed by JSON pointer
+///
+/// Paths follow RFC 6901: the root is `""`, and `~` and `/` in keys are
+/// escaped as `~0` and `~1`. Entries are kept in document order.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct DecodeTrace {
+    forms: Vec<(String, ArrayForm)>,
+}
+
+impl DecodeTrace {
+    /// The form of the array at `pointer`, if one was decoded there
+    pub fn get(&self, pointer: &str) -> Option<ArrayForm> {
+        self.forms
+            .iter()
+            .find(|(path, _)| path == pointer)
+            .map(|(_, form)| *form)
+    }
+
+    /// Iterate over `(pointer, form)` pairs in document order
+    pub fn iter(&self) -> impl Iterator<Item = (&str, ArrayForm)> {
+        self.forms.iter().map(|(path, form)| (path.as_str(), *form))
+    }
+}
+
+/// Current JSON pointer and the forms recorded so far
+#[derive(Default)]
+struct Tracer {
+    path: String,
+    trace: DecodeTrace,
 }
 
 #[derive(Debug, Clone)]
@@ -40,44 +750,200 @@ struct Line {
     content: String,
     depth: usize,
     line_num: usize,
+    /// Byte offset of `content` within the original input
+    offset: usize,
 }
 
 impl<'a> Decoder<'a> {
     fn new(input: &str, options: &'a DecoderOptions) -> Result<Self, DecodeError> {
-        let lines = Self::parse_lines(input, options)?;
+        let (lines, errors) = Self::parse_lines(input, options);
+        if let Some(err) = errors.into_iter().next() {
+            return Err(err);
+        }
         Ok(Self {
             lines,
             options,
             pos: 0,
+            collected: None,
+            trace: None,
+            warnings: None,
+            pending_item: None,
         })
     }
 
     /// Parse input into lines with depth information
-    fn parse_lines(input: &str, options: &DecoderOptions) -> Result<Vec<Line>, DecodeError> {
-        input
-            .lines()
+    ///
+    /// Line-level errors are returned alongside the lines so callers can either
+    /// fail on the first one or collect them all.
+    fn parse_lines(input: &str, options: &DecoderOptions) -> (Vec<Line>, Vec<DecodeError>) {
+        Self::parse_segment(input, input, 1, options)
+    }
+
+    /// Parse `segment`, a slice of `input` starting at line `first_line`, into lines
+    ///
+    /// Line numbers and byte offsets are relative to the whole `input`.
+    fn parse_segment(
+        input: &str,
+        segment: &str,
+        first_line: usize,
+        options: &DecoderOptions,
+    ) -> (Vec<Line>, Vec<DecodeError>) {
+        // A leading byte order mark (common from Windows editors) is not content
+        let segment = segment.strip_prefix('\u{FEFF}').unwrap_or(segment);
+
+        let prefix = if options.auto_dedent {
+            Self::split_lines(segment).find_map(|line| Self::dedent_prefix(line, options))
+        } else {
+            None
+        };
+        let lines = || {
+            Self::split_lines(segment)
+                .map(move |line| prefix.and_then(|p| line.strip_prefix(p)).unwrap_or(line))
+        };
+
+        let inferred;
+        let options = match lines().find_map(|line| Self::inferred_indent(line, options)) {
+            Some(width) => {
+                inferred = DecoderOptions {
+                    indent: IndentStyle::Spaces(width),
+                    ..options.clone()
+                };
+                &inferred
+            }
+            None => options,
+        };
+
+        let mut errors = Vec::new();
+        let lines = lines()
             .enumerate()
             .filter_map(|(i, line)| {
-                // Skip completely blank lines outside structures
-                if line.trim().is_empty() {
-                    return None;
+                // `lines` yields subslices of `input`, so the pointer distance is the byte offset
+                let offset = line.as_ptr() as usize - input.as_ptr() as usize;
+                Self::parse_line(line, first_line + i, offset, options, &mut errors)
+            })
+            .collect();
+        (lines, errors)
+    }
+
+    /// Split input into lines ending in `\n`, `\r\n` or a lone `\r`
+    ///
+    /// Like `str::lines`, a final terminator does not start an extra empty line.
+    fn split_lines(input: &str) -> impl Iterator<Item = &str> {
+        let mut rest = input;
+        core::iter::from_fn(move || {
+            if rest.is_empty() {
+                return None;
+            }
+            let (line, next) = match rest.find(['\n', '\r']) {
+                Some(end) => {
+                    let terminator = if rest[end..].starts_with("\r\n") {
+                        2
+                    } else {
+                        1
+                    };
+                    (&rest[..end], &rest[end + terminator..])
+                }
+                None => (rest, ""),
+            };
+            rest = next;
+            Some(line)
+        })
+    }
+
+    /// Indent width implied by `line` when lenient decoding should infer it
+    ///
+    /// Returns the leading space count of an indented content line if `strict`
+    /// is off and indentation is space-based; `None` otherwise.
+    fn inferred_indent(line: &str, options: &DecoderOptions) -> Option<usize> {
+        if options.strict || options.indent == IndentStyle::Tabs {
+            return None;
+        }
+
+        let content = line.trim_start();
+        if content.is_empty() || (options.allow_comments && content.starts_with('#')) {
+            return None;
+        }
+
+        let leading = Self::indent_len(line);
+        (leading > 0).then_some(leading)
+    }
+
+    /// Leading indentation of a content line, for `auto_dedent`
+    ///
+    /// Returns `None` for blank lines and, with `allow_comments`, comments.
+    fn dedent_prefix<'l>(line: &'l str, options: &DecoderOptions) -> Option<&'l str> {
+        let content = line.trim_start();
+        if content.is_empty() || (options.allow_comments && content.starts_with('#')) {
+            return None;
+        }
+        Some(&line[..Self::indent_len(line)])
+    }
+
+    /// Byte length of the leading ASCII spaces and tabs of a line
+    fn indent_len(line: &str) -> usize {
+        line.len() - line.trim_start_matches([' ', '\t']).len()
+    }
+
+    /// Parse a single raw line, returning `None` for lines that carry no content
+    ///
+    /// Errors are pushed to `errors`; the line is still returned with a
+    /// best-effort depth so that parsing can continue.
+    fn parse_line(
+        line: &str,
+        line_num: usize,
+        offset: usize,
+        options: &DecoderOptions,
+        errors: &mut Vec<DecodeError>,
+    ) -> Option<Line> {
+        // Skip completely blank lines outside structures
+        if line.trim().is_empty() {
+            return None;
+        }
+
+        // Skip full-line comments before they affect depth analysis
+        if options.allow_comments && line.trim_start().starts_with('#') {
+            return None;
+        }
+
+        // Only ASCII spaces and tabs indent; other Unicode whitespace such as
+        // a no-break space is not counted and is invalid in strict mode
+        let leading = &line[..Self::indent_len(line)];
+        let other_whitespace = line[leading.len()..].starts_with(char::is_whitespace);
+
+        let depth = match options.indent {
+            IndentStyle::Spaces(width) => {
+                let leading_spaces = leading.len();
+
+                // Validate indentation in strict mode; a tab would silently
+                // count as a single space, so it is reported on its own
+                if options.strict && leading.bytes().any(|b| b != b' ') {
+                    errors.push(DecodeError::MixedIndentation { line: line_num });
+                } else if options.strict
+                    && (other_whitespace || !leading_spaces.is_multiple_of(width))
+                {
+                    errors.push(DecodeError::InvalidIndentation { line: line_num });
                 }
 
-                let leading_spaces = line.len() - line.trim_start().len();
+                leading_spaces / width
+            }
+            IndentStyle::Tabs => {
+                let leading_tabs = line.len() - line.trim_start_matches('\t').len();
 
-                // Validate indentation in strict mode
-                if options.strict && leading_spaces % options.indent != 0 {
-                    return Some(Err(DecodeError::InvalidIndentation { line: i + 1 }));
+                // Anything other than tabs before the content is invalid in strict mode
+                let rest = &line[leading_tabs..];
+                if options.strict && rest.len() != rest.trim_start().len() {
+                    errors.push(DecodeError::InvalidIndentation { line: line_num });
                 }
 
-                let depth = leading_spaces / options.indent;
-                Some(Ok(Line {
-                    content: line.trim().to_string(),
-                    depth,
-                    line_num: i + 1,
-                }))
-            })
-            .collect()
+                leading_tabs
+            }
+        };
+        Some(Line {
+            content: line.trim().to_string(),
+            depth,
+            line_num,
+            offset: offset + line.len() - line.trim_start().len(),
+        })
     }
 
     fn decode(&mut self) -> Result<Value, DecodeError> {
@@ -88,7 +954,23 @@ impl<'a> Decoder<'a> {
 
         // Determine root form (§5)
         if self.is_root_array() {
-            self.decode_array(0)
+            let value = self.decode_array(0)?;
+            // Nothing may follow the root array
+            if let Some(line) = self.lines.get(self.pos) {
+                if self.options.strict {
+                    self.report(if line.depth > 0 {
+                        DecodeError::InvalidIndentation {
+                            line: line.line_num,
+                        }
+                    } else {
+                        DecodeError::InvalidLine {
+                            line: line.line_num,
+                            content: line.content.clone(),
+                        }
+                    })?;
+                }
+            }
+            Ok(value)
         } else if self.lines.len() == 1 && !self.is_key_value(&self.lines[0].content) {
             // Single primitive line
             Ok(self.parse_primitive(&self.lines[0].content, self.lines[0].line_num)?)
@@ -104,20 +986,28 @@ impl<'a> Decoder<'a> {
             return false;
         }
         let content = &self.lines[0].content;
-        content.starts_with('[') && content.contains("]:")
+        Self::is_array_bracket(content) && Self::find_unquoted(content, ':').is_some()
+    }
+
+    /// Check if `s` opens with a valid length bracket: `[`, optional digits,
+    /// an optional delimiter symbol (including an explicit `,`), then `]`
+    ///
+    /// This keeps text such as `[draft]: notes` from being taken for a header.
+    fn is_array_bracket(s: &str) -> bool {
+        let Some(rest) = s.strip_prefix('[') else {
+            return false;
+        };
+        let Some(close) = rest.find(']') else {
+            return false;
+        };
+        let content = &rest[..close];
+        let digits = content.trim_end_matches(['\t', '|', ';', ',']);
+        content.len() - digits.len() <= 1 && digits.chars().all(|c| c.is_ascii_digit())
     }
 
     /// Check if line is key-value format (has unquoted colon)
     fn is_key_value(&self, line: &str) -> bool {
-        let mut in_quotes = false;
-        for ch in line.chars() {
-            if ch == '"' {
-                in_quotes = !in_quotes;
-            } else if ch == ':' && !in_quotes {
-                return true;
-            }
-        }
-        false
+        Self::find_unquoted(line, ':').is_some()
     }
 
     /// Decode an object starting at given depth
@@ -144,7 +1034,11 @@ impl<'a> Decoder<'a> {
             }
 
             if depth > start_depth {
-                // Skip - handled by nested structure
+                // Nested structures consume their own lines, so this one has no
+                // owning key (e.g. it follows a primitive value)
+                if self.options.strict {
+                    self.report(DecodeError::InvalidIndentation { line: line_num })?;
+                }
                 self.pos += 1;
                 continue;
             }
@@ -153,22 +1047,23 @@ impl<'a> Decoder<'a> {
             let content = self.lines[self.pos].content.clone();
 
             // Parse key-value at this depth
-            if let Some((key, value_part)) = self.parse_key_value(&content, line_num)? {
+            if let Some(colon_pos) = Self::find_unquoted(&content, ':') {
                 self.pos += 1;
+                let key = content[..colon_pos].trim();
+                let value_part = content[colon_pos + 1..].trim().to_string();
 
                 // Check if key contains array header (e.g., "tags[3]")
-                let (actual_key, array_header) = if key.contains('[') {
-                    if let Some(bracket_pos) = key.find('[') {
-                        let k = &key[..bracket_pos];
-                        let h = &key[bracket_pos..];
-                        (k.to_string(), Some(h.to_string()))
-                    } else {
-                        (key.clone(), None)
+                let (key_part, array_header) = match Self::array_key_bracket(key) {
+                    Some(bracket_pos) => {
+                        (&key[..bracket_pos], Some(key[bracket_pos..].to_string()))
                     }
-                } else {
-                    (key.clone(), None)
+                    None => (key, None),
                 };
+                let actual_key = self
+                    .unescape_string_cow(key_part.trim_end(), line_num)?
+                    .into_owned();
 
+                let mark = self.trace_key(&actual_key);
                 let value = if let Some(header) = array_header {
                     // Key has array header - parse as array
                     let full_header = if value_part.is_empty() {
@@ -182,10 +1077,10 @@ impl<'a> Decoder<'a> {
                     {
                         array_value
                     } else {
-                        return Err(DecodeError::InvalidArrayHeader(format!(
-                            "Invalid array header in key: {}",
-                            key
-                        )));
+                        return Err(DecodeError::InvalidArrayHeader {
+                            line: line_num,
+                            message: format!("Invalid array header in key: {}", key),
+                        });
                     }
                 } else if value_part.is_empty() {
                     // Nested object or empty object
@@ -194,12 +1089,16 @@ impl<'a> Decoder<'a> {
                     } else {
                         Value::Object(serde_json::Map::new())
                     }
+                } else if let Some(block) = self.try_decode_block_string(&value_part, start_depth) {
+                    // Multi-line `|` block string
+                    block
                 } else {
-                    // Primitive value
-                    self.parse_primitive(&value_part, line_num)?
+                    // Primitive value or inline object
+                    self.parse_field_value(&value_part, line_num)?
                 };
+                self.trace_leave(mark);
 
-                obj.insert(actual_key, value);
+                self.insert_field(&mut obj, actual_key, value, line_num)?;
             } else {
                 return Err(DecodeError::InvalidLine {
                     line: line_num,
@@ -224,12 +1123,33 @@ impl<'a> Decoder<'a> {
 
         let (length, delimiter, fields) = self.parse_array_header(header_part, line_num)?;
 
-        // Check if inline values follow
-        if let Some(colon_pos) = header_part.find(':') {
+        // Check if inline values follow (quoted field names may contain ':')
+        if let Some(colon_pos) = Self::find_unquoted(header_part, ':') {
             let after_colon = header_part[colon_pos + 1..].trim();
 
-            if !after_colon.is_empty() {
+            if self.options.same_line_list_items
+                && (after_colon.starts_with("- ") || after_colon == "-")
+                && fields.is_empty()
+            {
+                // First list item on the header line (`array_on_same_line`):
+                // read it as if it started the next line, then continue as a list.
+                // The item is the tail of the header line, the last one consumed
+                let offset = self.lines[..self.pos].last().map_or(0, |line| {
+                    if line.content.ends_with(after_colon) {
+                        line.offset + line.content.len() - after_colon.len()
+                    } else {
+                        line.offset
+                    }
+                });
+                self.pending_item = Some(Line {
+                    content: after_colon.to_string(),
+                    depth: parent_depth + 1,
+                    line_num,
+                    offset,
+                });
+            } else if !after_colon.is_empty() {
                 // Inline primitive array
+                self.trace_form(ArrayForm::Inline);
                 return Ok(Some(self.decode_inline_array(
                     after_colon,
                     delimiter,
@@ -242,18 +1162,22 @@ impl<'a> Decoder<'a> {
         // Check for tabular or list format
         if !fields.is_empty() {
             // Tabular format
+            self.trace_form(ArrayForm::Tabular);
             Ok(Some(self.decode_tabular_array(
                 parent_depth + 1,
                 length,
                 delimiter,
                 &fields,
+                line_num,
             )?))
         } else {
             // List format
+            self.trace_form(ArrayForm::List);
             Ok(Some(self.decode_list_array(
                 parent_depth + 1,
                 length,
                 delimiter,
+                line_num,
             )?))
         }
     }
@@ -263,33 +1187,43 @@ impl<'a> Decoder<'a> {
         &self,
         header: &str,
         line_num: usize,
-    ) -> Result<(usize, Delimiter, Vec<String>), DecodeError> {
-        let bracket_end = header.find(']').ok_or_else(|| {
-            DecodeError::InvalidArrayHeader("Missing ] in array header".to_string())
-        })?;
+    ) -> Result<(Option<usize>, Delimiter, Vec<String>), DecodeError> {
+        let bracket_end = header
+            .find(']')
+            .ok_or_else(|| DecodeError::InvalidArrayHeader {
+                line: line_num,
+                message: "Missing ] in array header".to_string(),
+            })?;
         let bracket_content = &header[1..bracket_end];
 
         // Parse length and delimiter
-        let (length, delimiter) = if let Some(stripped) = bracket_content.strip_suffix('\t') {
-            (
-                stripped.parse().map_err(|_| {
-                    DecodeError::InvalidArrayHeader("Invalid array length".to_string())
-                })?,
-                Delimiter::Tab,
-            )
-        } else if let Some(stripped) = bracket_content.strip_suffix('|') {
-            (
-                stripped.parse().map_err(|_| {
-                    DecodeError::InvalidArrayHeader("Invalid array length".to_string())
-                })?,
-                Delimiter::Pipe,
-            )
+        let (length_str, delimiter) = match self.options.spec_version {
+            SpecVersion::V2_0 => {
+                if let Some(stripped) = bracket_content.strip_suffix('\t') {
+                    (stripped, Delimiter::Tab)
+                } else if let Some(stripped) = bracket_content.strip_suffix('|') {
+                    (stripped, Delimiter::Pipe)
+                } else if let Some(stripped) = bracket_content.strip_suffix(';') {
+                    (stripped, Delimiter::Semicolon)
+                } else if let Some(stripped) = bracket_content.strip_suffix(',') {
+                    // Comma is the default, but an explicit marker is accepted
+                    (stripped, Delimiter::Comma)
+                } else {
+                    (bracket_content, Delimiter::Comma)
+                }
+            }
+        };
+        // An omitted length (`[]`, `[|]`) leaves the item count unchecked
+        let length = if length_str.is_empty() {
+            None
         } else {
-            (
-                bracket_content.parse().map_err(|_| {
-                    DecodeError::InvalidArrayHeader("Invalid array length".to_string())
-                })?,
-                Delimiter::Comma,
+            Some(
+                length_str
+                    .parse()
+                    .map_err(|_| DecodeError::InvalidArrayHeader {
+                        line: line_num,
+                        message: "Invalid array length".to_string(),
+                    })?,
             )
         };
 
@@ -297,7 +1231,8 @@ impl<'a> Decoder<'a> {
         let mut fields = Vec::new();
         let after_bracket = &header[bracket_end + 1..];
         if after_bracket.starts_with('{') {
-            if let Some(close_brace) = after_bracket.find('}') {
+            // Quoted field names may contain '}', so match the brace quote-aware
+            if let Some(close_brace) = Self::find_unquoted(after_bracket, '}') {
                 let fields_str = &after_bracket[1..close_brace];
                 fields = self
                     .split_by_delimiter(fields_str, delimiter)
@@ -310,23 +1245,56 @@ impl<'a> Decoder<'a> {
         Ok((length, delimiter, fields))
     }
 
+    /// Check an array's item count against its declared length (strict mode only)
+    fn check_length(
+        &self,
+        expected: Option<usize>,
+        found: usize,
+        header_line: usize,
+    ) -> Result<(), DecodeError> {
+        match expected {
+            Some(expected) if self.options.strict && found != expected => {
+                self.report(DecodeError::ArrayLengthMismatch {
+                    line: header_line,
+                    expected,
+                    found,
+                })
+            }
+            Some(expected) if found != expected => {
+                self.warn(Warning::ArrayLengthMismatch {
+                    line: header_line,
+                    expected,
+                    found,
+                });
+                Ok(())
+            }
+            _ => Ok(()),
+        }
+    }
+
     /// Decode inline primitive array
     fn decode_inline_array(
         &self,
         values_str: &str,
         delimiter: Delimiter,
-        expected_len: usize,
+        expected_len: Option<usize>,
         line_num: usize,
     ) -> Result<Value, DecodeError> {
-        let values = self.split_by_delimiter(values_str, delimiter);
-
-        if self.options.strict && values.len() != expected_len {
-            return Err(DecodeError::ArrayLengthMismatch {
-                expected: expected_len,
-                found: values.len(),
-            });
+        // An empty value list (e.g. `- [0]:`) is an empty array, not one empty string
+        let mut values = if values_str.is_empty() {
+            Vec::new()
+        } else {
+            self.split_by_delimiter(values_str, delimiter)
+        };
+        if self.options.allow_trailing_delimiter
+            && values.len() > 1
+            && values.last().is_some_and(|v| v.is_empty())
+        {
+            values.pop();
         }
 
+        self.check_length(expected_len, values.len(), line_num)?;
+
         let arr: Result<Vec<Value>, _> = values
             .iter()
             .map(|v| self.parse_primitive(v, line_num))
@@ -336,46 +1304,87 @@ impl<'a> Decoder<'a> {
     }
 
     /// Decode tabular array
+    ///
+    /// Cells hold primitives only. An unquoted cell shaped like an inline
+    /// array header (`[2]: 10,20`) is rejected, since its values would collide
+    /// with the row delimiter; quoting the cell keeps it as a string.
     fn decode_tabular_array(
         &mut self,
         row_depth: usize,
-        expected_rows: usize,
+        expected_rows: Option<usize>,
         delimiter: Delimiter,
         fields: &[String],
+        header_line: usize,
     ) -> Result<Value, DecodeError> {
         let mut arr = Vec::new();
 
         while self.pos < self.lines.len() && self.lines[self.pos].depth == row_depth {
             let line = &self.lines[self.pos];
-            let values = self.split_by_delimiter(&line.content, delimiter);
+            let mut values = self.split_by_delimiter(&line.content, delimiter);
+            if self.options.sniff_delimiter && values.len() != fields.len() {
+                if let Some(sniffed) = Delimiter::all()
+                    .iter()
+                    .filter(|&&d| d != delimiter)
+                    .map(|&d| self.split_by_delimiter(&line.content, d))
+                    .find(|cells| cells.len() == fields.len())
+                {
+                    values = sniffed;
+                }
+            }
 
-            if self.options.strict && values.len() != fields.len() {
-                return Err(DecodeError::RowWidthMismatch {
+            if let Some(cell) = values
+                .iter()
+                .find(|cell| Self::is_array_bracket(cell) && cell.contains(':'))
+            {
+                self.report(DecodeError::ParseError {
                     line: line.line_num,
-                    expected: fields.len(),
-                    found: values.len(),
-                });
+                    message: format!(
+                        "Tabular cells cannot hold arrays (found `{}`); quote the cell to keep it as a string",
+                        cell
+                    ),
+                })?;
+            }
+
+            let short_allowed = self.options.ragged_rows && values.len() < fields.len();
+            if values.len() != fields.len() {
+                if self.options.strict && !short_allowed {
+                    self.report(DecodeError::RowWidthMismatch {
+                        line: line.line_num,
+                        expected: fields.len(),
+                        found: values.len(),
+                    })?;
+                } else {
+                    self.warn(Warning::RowWidthMismatch {
+                        line: line.line_num,
+                        expected: fields.len(),
+                        found: values.len(),
+                    });
+                }
             }
 
             let mut obj = serde_json::Map::new();
             for (i, field) in fields.iter().enumerate() {
-                if i < values.len() {
-                    obj.insert(
-                        field.clone(),
-                        self.parse_primitive(&values[i], line.line_num)?,
-                    );
-                }
+                // Missing trailing cells in ragged rows become null
+                let field_type = self
+                    .options
+                    .field_types
+                    .as_ref()
+                    .and_then(|types| types.get(field));
+                let value = match values.get(i) {
+                    Some(cell) if cell.is_empty() && self.options.empty_cell_as_null => Value::Null,
+                    Some(cell) => match field_type {
+                        Some(&ty) => self.coerce_cell(cell, field, ty, line.line_num)?,
+                        None => self.parse_primitive(cell, line.line_num)?,
+                    },
+                    None => Value::Null,
+                };
+                obj.insert(field.clone(), value);
             }
             arr.push(Value::Object(obj));
             self.pos += 1;
         }
 
-        if self.options.strict && arr.len() != expected_rows {
-            return Err(DecodeError::ArrayLengthMismatch {
-                expected: expected_rows,
-                found: arr.len(),
-            });
-        }
+        self.check_length(expected_rows, arr.len(), header_line)?;
 
         Ok(Value::Array(arr))
     }
@@ -391,10 +1400,13 @@ impl<'a> Decoder<'a> {
         let mut obj = serde_json::Map::new();
 
         // Process first field
+        let mark = self.trace_key(&first_key);
         if first_value.is_empty() {
-            // Nested structure
+            // Nested structure - the first field shares the hyphen line, so its
+            // children are indented relative to that position rather than item_depth
             if self.pos < self.lines.len() && self.lines[self.pos].depth > item_depth {
-                obj.insert(first_key, self.decode_object(item_depth + 1, None)?);
+                let child_depth = self.lines[self.pos].depth;
+                obj.insert(first_key, self.decode_object(child_depth, None)?);
             } else {
                 obj.insert(first_key, Value::Object(serde_json::Map::new()));
             }
@@ -402,31 +1414,40 @@ impl<'a> Decoder<'a> {
             self.try_parse_array_header(&first_value, item_depth, line_num)?
         {
             obj.insert(first_key, arr_val);
+        } else if let Some(block) = self.try_decode_block_string(&first_value, item_depth) {
+            obj.insert(first_key, block);
         } else {
-            obj.insert(first_key, self.parse_primitive(&first_value, line_num)?);
+            obj.insert(first_key, self.parse_field_value(&first_value, line_num)?);
         }
+        self.trace_leave(mark);
 
         // Process remaining fields at item_depth
         while self.pos < self.lines.len()
             && self.lines[self.pos].depth == item_depth
             && !self.lines[self.pos].content.starts_with("- ")
+            && self.lines[self.pos].content != "-"
         {
             let field_line = &self.lines[self.pos].clone();
-            if let Some((k, v)) = self.parse_key_value(&field_line.content, field_line.line_num)? {
+            if let Some((k, v)) = self.parse_item_field(&field_line.content, field_line.line_num)? {
                 self.pos += 1;
-                if v.is_empty() {
+                let mark = self.trace_key(&k);
+                let value = if v.is_empty() {
                     if self.pos < self.lines.len() && self.lines[self.pos].depth > item_depth {
-                        obj.insert(k, self.decode_object(item_depth + 1, None)?);
+                        self.decode_object(item_depth + 1, None)?
                     } else {
-                        obj.insert(k, Value::Object(serde_json::Map::new()));
+                        Value::Object(serde_json::Map::new())
                     }
                 } else if let Some(arr_val) =
                     self.try_parse_array_header(&v, item_depth, field_line.line_num)?
                 {
-                    obj.insert(k, arr_val);
+                    arr_val
+                } else if let Some(block) = self.try_decode_block_string(&v, item_depth) {
+                    block
                 } else {
-                    obj.insert(k, self.parse_primitive(&v, field_line.line_num)?);
-                }
+                    self.parse_field_value(&v, field_line.line_num)?
+                };
+                self.trace_leave(mark);
+                self.insert_field(&mut obj, k, value, field_line.line_num)?;
             } else {
                 break;
             }
@@ -435,71 +1456,187 @@ impl<'a> Decoder<'a> {
         Ok(obj)
     }
 
-    /// Decode list array (expanded format)
-    fn decode_list_array(
-        &mut self,
-        item_depth: usize,
-        expected_len: usize,
-        _delimiter: Delimiter,
-    ) -> Result<Value, DecodeError> {
-        let mut arr = Vec::new();
-
-        while self.pos < self.lines.len() && self.lines[self.pos].depth == item_depth {
-            let line = self.lines[self.pos].clone();
-
-            if !line.content.starts_with("- ") {
-                break;
-            }
+    /// Decode a `|` block string whose lines are indented deeper than `parent_depth`
+    ///
+    /// Returns `None` (consuming nothing) unless `value_part` is `|` and at least
+    /// one deeper line follows; a lone `|` with no block is an ordinary string.
+    fn try_decode_block_string(&mut self, value_part: &str, parent_depth: usize) -> Option<Value> {
+        if value_part != "|" {
+            return None;
+        }
 
-            let item_content = &line.content[2..];
+        let start = self.pos;
+        while self.pos < self.lines.len() && self.lines[self.pos].depth > parent_depth {
             self.pos += 1;
-
-            let value = if item_content.starts_with('[') {
-                // Inline array item
-                let (length, delim, _) = self.parse_array_header(item_content, line.line_num)?;
-                if let Some(colon_pos) = item_content.find(':') {
-                    let after_colon = item_content[colon_pos + 1..].trim();
-                    self.decode_inline_array(after_colon, delim, length, line.line_num)?
-                } else {
-                    Value::Null
-                }
-            } else if let Some((key, value_part)) =
-                self.parse_key_value(item_content, line.line_num)?
-            {
-                // Object as list item - decode it without mutating internal state
-                let obj =
-                    self.decode_list_item_object(key, value_part, item_depth, line.line_num)?;
-                Value::Object(obj)
-            } else {
-                // Primitive item
-                self.parse_primitive(item_content, line.line_num)?
-            };
-
-            arr.push(value);
         }
-
-        if self.options.strict && arr.len() != expected_len {
-            return Err(DecodeError::ArrayLengthMismatch {
-                expected: expected_len,
-                found: arr.len(),
-            });
+        if self.pos == start {
+            return None;
         }
 
-        Ok(Value::Array(arr))
+        let text = self.lines[start..self.pos]
+            .iter()
+            .map(|line| line.content.as_str())
+            .collect::<Vec<_>>()
+            .join("\n");
+        Some(Value::String(text))
     }
 
-    /// Decode root array
-    fn decode_array(&mut self, depth: usize) -> Result<Value, DecodeError> {
-        let line = &self.lines[0];
-        let (length, delimiter, fields) = self.parse_array_header(&line.content, line.line_num)?;
-
-        self.pos = 1;
-
-        if !fields.is_empty() {
-            self.decode_tabular_array(depth + 1, length, delimiter, &fields)
-        } else {
-            self.decode_list_array(depth + 1, length, delimiter)
+    /// Insert a decoded field, rejecting duplicate keys in strict mode
+    ///
+    /// In lenient mode a repeated key overwrites the earlier value (last wins).
+    fn insert_field(
+        &self,
+        obj: &mut serde_json::Map<String, Value>,
+        key: String,
+        value: Value,
+        line_num: usize,
+    ) -> Result<(), DecodeError> {
+        if self.options.strict && obj.contains_key(&key) {
+            self.report(DecodeError::DuplicateKey {
+                line: line_num,
+                key: key.clone(),
+            })?;
         }
+        obj.insert(key, value);
+        Ok(())
+    }
+
+    /// Report a recoverable error
+    ///
+    /// When validating, the error is recorded and decoding continues with a
+    /// best-effort value; otherwise it is returned to abort decoding.
+    fn report(&self, err: DecodeError) -> Result<(), DecodeError> {
+        match &self.collected {
+            Some(collected) => {
+                collected.borrow_mut().push(err);
+                Ok(())
+            }
+            None => Err(err),
+        }
+    }
+
+    /// Descend into an object field when tracing, returning the path to restore
+    fn trace_key(&mut self, key: &str) -> usize {
+        let Some(tracer) = &mut self.trace else {
+            return 0;
+        };
+        let mark = tracer.path.len();
+        tracer.path.push('/');
+        for c in key.chars() {
+            match c {
+                '~' => tracer.path.push_str("~0"),
+                '/' => tracer.path.push_str("~1"),
+                c => tracer.path.push(c),
+            }
+        }
+        mark
+    }
+
+    /// Descend into an array item when tracing, returning the path to restore
+    fn trace_index(&mut self, index: usize) -> usize {
+        let Some(tracer) = &mut self.trace else {
+            return 0;
+        };
+        let mark = tracer.path.len();
+        let _ = write!(tracer.path, "/{}", index);
+        mark
+    }
+
+    /// Return to the path saved by `trace_key` or `trace_index`
+    fn trace_leave(&mut self, mark: usize) {
+        if let Some(tracer) = &mut self.trace {
+            tracer.path.truncate(mark);
+        }
+    }
+
+    /// Record the form of the array at the current path
+    fn trace_form(&mut self, form: ArrayForm) {
+        if let Some(tracer) = &mut self.trace {
+            let path = tracer.path.clone();
+            tracer.trace.forms.push((path, form));
+        }
+    }
+
+    /// Record a tolerated problem when collecting warnings
+    fn warn(&self, warning: Warning) {
+        if let Some(warnings) = &self.warnings {
+            warnings.borrow_mut().push(warning);
+        }
+    }
+
+    /// Decode list array (expanded format)
+    fn decode_list_array(
+        &mut self,
+        item_depth: usize,
+        expected_len: Option<usize>,
+        _delimiter: Delimiter,
+        header_line: usize,
+    ) -> Result<Value, DecodeError> {
+        let mut arr = Vec::new();
+        let mut pending = self.pending_item.take();
+
+        loop {
+            let line = match pending.take() {
+                Some(line) => line,
+                None if self.pos < self.lines.len()
+                    && self.lines[self.pos].depth == item_depth
+                    && (self.lines[self.pos].content == "-"
+                        || self.lines[self.pos].content.starts_with("- ")) =>
+                {
+                    self.pos += 1;
+                    self.lines[self.pos - 1].clone()
+                }
+                None => break,
+            };
+
+            if line.content == "-" {
+                // A bare hyphen, like a bare `key:`, is an empty object
+                arr.push(Value::Object(serde_json::Map::new()));
+                continue;
+            }
+            let item_content = &line.content[2..];
+
+            let mark = self.trace_index(arr.len());
+            let array = if Self::is_array_bracket(item_content) {
+                self.try_parse_array_header(item_content, item_depth, line.line_num)?
+            } else {
+                None
+            };
+            let value = if let Some(array) = array {
+                // Array item: inline, or nested items one level deeper
+                array
+            } else if let Some((key, value_part)) =
+                self.parse_item_field(item_content, line.line_num)?
+            {
+                // Object as list item - decode it without mutating internal state
+                let obj =
+                    self.decode_list_item_object(key, value_part, item_depth, line.line_num)?;
+                Value::Object(obj)
+            } else {
+                // Primitive item
+                self.parse_primitive(item_content, line.line_num)?
+            };
+            self.trace_leave(mark);
+
+            arr.push(value);
+        }
+
+        self.check_length(expected_len, arr.len(), header_line)?;
+
+        Ok(Value::Array(arr))
+    }
+
+    /// Decode root array
+    fn decode_array(&mut self, depth: usize) -> Result<Value, DecodeError> {
+        let content = self.lines[0].content.clone();
+        let line_num = self.lines[0].line_num;
+        self.pos = 1;
+
+        // Root arrays take the same inline, tabular and list forms as keyed
+        // arrays; `is_root_array` guarantees the header is present
+        Ok(self
+            .try_parse_array_header(&content, depth, line_num)?
+            .unwrap_or_default())
     }
 
     /// Parse key: value line
@@ -508,19 +1645,8 @@ impl<'a> Decoder<'a> {
         line: &str,
         line_num: usize,
     ) -> Result<Option<(String, String)>, DecodeError> {
-        let mut in_quotes = false;
-        let mut colon_pos = None;
-
-        for (i, ch) in line.chars().enumerate() {
-            if ch == '"' && (i == 0 || line.chars().nth(i - 1) != Some('\\')) {
-                in_quotes = !in_quotes;
-            } else if ch == ':' && !in_quotes {
-                colon_pos = Some(i);
-                break;
-            }
-        }
-
-        if let Some(pos) = colon_pos {
+        // Byte offset, so slicing below stays on a char boundary
+        if let Some(pos) = Self::find_unquoted(line, ':') {
             let key = line[..pos].trim();
             let value = line[pos + 1..].trim();
 
@@ -531,6 +1657,109 @@ impl<'a> Decoder<'a> {
         }
     }
 
+    /// Parse a field of a list-item object, moving an array header on the key
+    /// into the value (`tags[2]: a,b` gives `tags` and `[2]: a,b`)
+    fn parse_item_field(
+        &self,
+        line: &str,
+        line_num: usize,
+    ) -> Result<Option<(String, String)>, DecodeError> {
+        let Some(pos) = Self::find_unquoted(line, ':') else {
+            return Ok(None);
+        };
+        let key = line[..pos].trim();
+        let value = line[pos + 1..].trim();
+        let (key, value) = match Self::array_key_bracket(key) {
+            Some(bracket_pos) => (
+                &key[..bracket_pos],
+                format!("{}:{}", &key[bracket_pos..], value),
+            ),
+            None => (key, value.to_string()),
+        };
+
+        let unescaped_key = self.unescape_string_cow(key.trim_end(), line_num)?;
+        Ok(Some((unescaped_key.into_owned(), value)))
+    }
+
+    /// Byte offset of the array header in a key such as `tags[3]`
+    ///
+    /// The split uses the raw key, so brackets inside a quoted key (`"x[0]"`)
+    /// stay part of it, and a key that merely starts with bracketed text
+    /// (`[draft]`) is not a header.
+    fn array_key_bracket(key: &str) -> Option<usize> {
+        Self::find_unquoted(key, '[').filter(|&pos| pos > 0 || Self::is_array_bracket(key))
+    }
+
+    /// Locate the input bytes an error refers to, narrowed to the offending token when known
+    fn error_span(&self, err: &DecodeError) -> Option<ErrorSpan> {
+        let line_num = err.line()?;
+        let line = self.lines.iter().find(|l| l.line_num == line_num)?;
+        let content = line.content.as_str();
+
+        let (start, end) = match err {
+            DecodeError::InvalidEscapeSequence { sequence, .. } => sequence
+                .chars()
+                .next()
+                .and_then(|c| Self::find_escape(content, c))
+                .unwrap_or((0, content.len())),
+            DecodeError::InvalidCharacter { ch, .. } => content
+                .find(*ch)
+                .map_or((0, content.len()), |i| (i, i + ch.len_utf8())),
+            DecodeError::DuplicateKey { .. } => {
+                let start = if content.starts_with("- ") { 2 } else { 0 };
+                let end = Self::find_unquoted(content, ':').unwrap_or(content.len());
+                (start, start + content[start..end].trim_end().len())
+            }
+            DecodeError::InvalidArrayHeader { .. } => match content.find('[') {
+                Some(open) => {
+                    let end = Self::find_unquoted(&content[open..], ':')
+                        .map_or(content.len(), |i| open + i);
+                    (open, end)
+                }
+                None => (0, content.len()),
+            },
+            _ => (0, content.len()),
+        };
+
+        Some(ErrorSpan {
+            start: line.offset + start,
+            end: line.offset + end,
+        })
+    }
+
+    /// Find the byte range of the escape `\\c` in `s`, skipping other escapes
+    fn find_escape(s: &str, c: char) -> Option<(usize, usize)> {
+        let mut chars = s.char_indices();
+        while let Some((i, ch)) = chars.next() {
+            if ch == '\\' {
+                match chars.next() {
+                    Some((j, next)) if next == c => return Some((i, j + c.len_utf8())),
+                    _ => {}
+                }
+            }
+        }
+        None
+    }
+
+    /// Find the byte offset of the first `target` outside double quotes
+    fn find_unquoted(s: &str, target: char) -> Option<usize> {
+        let mut in_quotes = false;
+        let mut escaped = false;
+
+        for (i, ch) in s.char_indices() {
+            if escaped {
+                escaped = false;
+            } else if ch == '\\' && in_quotes {
+                escaped = true;
+            } else if ch == '"' {
+                in_quotes = !in_quotes;
+            } else if ch == target && !in_quotes {
+                return Some(i);
+            }
+        }
+        None
+    }
+
     /// Split string by delimiter, respecting quotes
     fn split_by_delimiter(&self, s: &str, delimiter: Delimiter) -> Vec<String> {
         let mut result = Vec::new();
@@ -561,36 +1790,101 @@ impl<'a> Decoder<'a> {
         result
     }
 
+    /// Parse a single-line object field value: an inline object, an inferred
+    /// inline array or a primitive
+    fn parse_field_value(&self, s: &str, line_num: usize) -> Result<Value, DecodeError> {
+        if self.options.inline_objects {
+            if let Some(obj) = self.try_parse_inline_object(s, line_num)? {
+                return Ok(obj);
+            }
+        }
+        let trimmed = s.trim();
+        if self.options.infer_inline_arrays && Self::find_unquoted(trimmed, ',').is_some() {
+            return self.decode_inline_array(trimmed, Delimiter::Comma, None, line_num);
+        }
+        self.parse_primitive(s, line_num)
+    }
+
+    /// Parse an inline `{key: value, ...}` object, as written by the encoder's
+    /// `inline_small_objects`
+    ///
+    /// Returns `None` unless the text is a non-empty, comma-separated list of
+    /// `key: value` entries with primitive values, so other text in braces
+    /// stays a string.
+    fn try_parse_inline_object(
+        &self,
+        s: &str,
+        line_num: usize,
+    ) -> Result<Option<Value>, DecodeError> {
+        let Some(inner) = s.strip_prefix('{').and_then(|rest| rest.strip_suffix('}')) else {
+            return Ok(None);
+        };
+        if inner.trim().is_empty() {
+            return Ok(None);
+        }
+
+        let mut entries = Vec::new();
+        for entry in self.split_by_delimiter(inner, Delimiter::Comma) {
+            let Some(colon_pos) = Self::find_unquoted(&entry, ':') else {
+                return Ok(None);
+            };
+            let key = entry[..colon_pos].trim();
+            let value = entry[colon_pos + 1..].trim();
+            if key.is_empty()
+                || value.is_empty()
+                || value.starts_with('{')
+                || Self::find_unquoted(key, '[').is_some()
+            {
+                return Ok(None);
+            }
+            entries.push((key.to_string(), value.to_string()));
+        }
+
+        let mut obj = serde_json::Map::new();
+        for (key, value) in entries {
+            let key = self.unescape_string_cow(&key, line_num)?.into_owned();
+            let value = self.parse_primitive(&value, line_num)?;
+            self.insert_field(&mut obj, key, value, line_num)?;
+        }
+        Ok(Some(Value::Object(obj)))
+    }
+
     /// Parse primitive value
     fn parse_primitive(&self, s: &str, line_num: usize) -> Result<Value, DecodeError> {
         let trimmed = s.trim();
 
         // Quoted string
-        if trimmed.starts_with('"') && trimmed.ends_with('"') {
+        if trimmed.len() >= 2 && trimmed.starts_with('"') && trimmed.ends_with('"') {
             return Ok(Value::String(self.unescape_string(trimmed, line_num)?));
         }
+        if trimmed.starts_with('"') && self.options.strict {
+            self.report(DecodeError::ParseError {
+                line: line_num,
+                message: "Unterminated string: missing closing quote".to_string(),
+            })?;
+        }
+
+        // Raw control characters are only valid escaped inside quotes
+        if let Some(ch) = trimmed.chars().find(|c| c.is_control()) {
+            if self.options.strict {
+                self.report(DecodeError::InvalidCharacter { line: line_num, ch })?;
+            }
+        }
 
         // Booleans and null
         match trimmed {
             "true" => return Ok(Value::Bool(true)),
             "false" => return Ok(Value::Bool(false)),
-            "null" => return Ok(Value::Null),
+            _ if self.is_null_token(trimmed) => return Ok(Value::Null),
             _ => {}
         }
 
-        // Try parsing as number (reject leading zeros per spec)
-        if !trimmed.is_empty() && !trimmed.starts_with('0')
-            || trimmed == "0"
-            || trimmed.starts_with("0.")
-            || trimmed.starts_with("-0")
-        {
-            if let Ok(i) = trimmed.parse::<i64>() {
-                return Ok(Value::Number(i.into()));
-            }
-            if let Ok(f) = trimmed.parse::<f64>() {
-                if let Some(num) = serde_json::Number::from_f64(f) {
-                    return Ok(Value::Number(num));
-                }
+        if let Some(num) = Self::parse_number(trimmed) {
+            return Ok(num);
+        }
+        if self.options.extended_number_literals {
+            if let Some(num) = Self::parse_extended_number(trimmed) {
+                return Ok(num);
             }
         }
 
@@ -598,6 +1892,132 @@ impl<'a> Decoder<'a> {
         Ok(Value::String(trimmed.to_string()))
     }
 
+    /// Parse a number in TOON number grammar, rejecting leading zeros per spec
+    fn parse_number(s: &str) -> Option<Value> {
+        if !is_numeric_literal(s) || has_leading_zeros(s) {
+            return None;
+        }
+        if let Ok(i) = s.parse::<i64>() {
+            return Some(Value::Number(i.into()));
+        }
+        if let Ok(u) = s.parse::<u64>() {
+            return Some(Value::Number(u.into()));
+        }
+        if is_integer_literal(s) {
+            // Beyond 64 bits: serde_json keeps the exact digits when its
+            // arbitrary_precision feature is enabled, otherwise this is an f64
+            if let Ok(num) = serde_json::from_str::<serde_json::Number>(s) {
+                return Some(Value::Number(num));
+            }
+        }
+        let f = s.parse::<f64>().ok()?;
+        serde_json::Number::from_f64(f).map(Value::Number)
+    }
+
+    /// Parse a radix-prefixed integer or a number with `_` separators, for
+    /// `extended_number_literals`
+    fn parse_extended_number(s: &str) -> Option<Value> {
+        let (negative, body) = match s.strip_prefix('-') {
+            Some(body) => (true, body),
+            None => (false, s),
+        };
+        let radix = match body.get(..2) {
+            Some("0x" | "0X") => 16,
+            Some("0o" | "0O") => 8,
+            Some("0b" | "0B") => 2,
+            _ => 10,
+        };
+        let digits = if radix == 10 { body } else { &body[2..] };
+
+        // Each separator needs a digit on both sides
+        let bytes = digits.as_bytes();
+        let separators_ok = bytes.iter().enumerate().all(|(i, &b)| {
+            b != b'_'
+                || (i > 0
+                    && (bytes[i - 1] as char).is_digit(radix)
+                    && bytes
+                        .get(i + 1)
+                        .is_some_and(|&n| (n as char).is_digit(radix)))
+        });
+        if !separators_ok {
+            return None;
+        }
+        let cleaned = digits.replace('_', "");
+
+        if radix == 10 {
+            // Without separators this is an ordinary number, already rejected
+            if cleaned.len() == digits.len() {
+                return None;
+            }
+            let sign = if negative { "-" } else { "" };
+            return Self::parse_number(&format!("{}{}", sign, cleaned));
+        }
+        if cleaned.is_empty() || !cleaned.chars().all(|c| c.is_digit(radix)) {
+            return None;
+        }
+        let magnitude = u64::from_str_radix(&cleaned, radix).ok()?;
+        if negative {
+            0i64.checked_sub_unsigned(magnitude)
+                .map(|i| Value::Number(i.into()))
+        } else {
+            Some(Value::Number(magnitude.into()))
+        }
+    }
+
+    /// Effective policy for unknown escapes, falling back to `strict`
+    fn escape_policy(&self) -> EscapePolicy {
+        match self.options.escape_policy {
+            Some(policy) => policy,
+            None if self.options.strict => EscapePolicy::Strict,
+            None => EscapePolicy::Preserve,
+        }
+    }
+
+    /// Check if an unquoted token is one of the configured `null` spellings
+    fn is_null_token(&self, s: &str) -> bool {
+        self.options.null_tokens.iter().any(|token| token == s)
+    }
+
+    /// Parse a tabular cell as the type configured for its field
+    fn coerce_cell(
+        &self,
+        cell: &str,
+        field: &str,
+        ty: ValueType,
+        line_num: usize,
+    ) -> Result<Value, DecodeError> {
+        if self.is_null_token(cell) {
+            return Ok(Value::Null);
+        }
+
+        let text = self.unescape_string_cow(cell, line_num)?;
+        let coerced = match ty {
+            ValueType::String => Some(Value::String(text.into_owned())),
+            ValueType::Number if is_numeric_literal(&text) => {
+                Some(self.parse_primitive(&text, line_num)?).filter(Value::is_number)
+            }
+            ValueType::Number => None,
+            ValueType::Bool => match text.as_ref() {
+                "true" => Some(Value::Bool(true)),
+                "false" => Some(Value::Bool(false)),
+                _ => None,
+            },
+        };
+
+        match coerced {
+            Some(value) => Ok(value),
+            None => {
+                if self.options.strict {
+                    self.report(DecodeError::ParseError {
+                        line: line_num,
+                        message: format!("Field `{}` expects {:?}, found `{}`", field, ty, cell),
+                    })?;
+                }
+                self.parse_primitive(cell, line_num)
+            }
+        }
+    }
+
     /// Unescape string with Cow optimization (remove quotes and handle escapes)
     /// Returns Cow::Borrowed if no unescaping is needed, Cow::Owned otherwise
     fn unescape_string_cow<'b>(
@@ -607,8 +2027,8 @@ impl<'a> Decoder<'a> {
     ) -> Result<Cow<'b, str>, DecodeError> {
         let trimmed = s.trim();
 
-        // If not quoted, return borrowed
-        if !trimmed.starts_with('"') || !trimmed.ends_with('"') {
+        // If not quoted, return borrowed (a lone `"` is not a quoted string)
+        if trimmed.len() < 2 || !trimmed.starts_with('"') || !trimmed.ends_with('"') {
             return Ok(Cow::Borrowed(trimmed));
         }
 
@@ -622,6 +2042,7 @@ impl<'a> Decoder<'a> {
         // Need to process escape sequences - allocate
         let mut result = String::with_capacity(inner.len());
         let mut chars = inner.chars();
+        let policy = self.escape_policy();
 
         while let Some(ch) = chars.next() {
             if ch == '\\' {
@@ -631,31 +2052,49 @@ impl<'a> Decoder<'a> {
                     Some('n') => result.push('\n'),
                     Some('r') => result.push('\r'),
                     Some('t') => result.push('\t'),
+                    Some('u') => match Self::decode_unicode_escape(&mut chars) {
+                        Ok(c) => result.push(c),
+                        Err(sequence) => {
+                            if policy == EscapePolicy::Strict {
+                                self.report(DecodeError::InvalidEscapeSequence {
+                                    line: line_num,
+                                    sequence,
+                                })?;
+                            }
+                            if policy != EscapePolicy::Unescape {
+                                result.push('\\');
+                            }
+                            result.push('u');
+                        }
+                    },
                     Some(other) => {
                         // Validate that the escape character is ASCII
-                        if !other.is_ascii() && self.options.strict {
-                            return Err(DecodeError::InvalidEscapeSequence {
+                        if policy == EscapePolicy::Strict {
+                            let sequence = if other.is_ascii() {
+                                other.to_string()
+                            } else {
+                                format!("{} (non-ASCII character in escape)", other)
+                            };
+                            self.report(DecodeError::InvalidEscapeSequence {
                                 line: line_num,
-                                sequence: format!("{} (non-ASCII character in escape)", other),
-                            });
+                                sequence,
+                            })?;
                         }
-
-                        if self.options.strict {
-                            return Err(DecodeError::InvalidEscapeSequence {
-                                line: line_num,
-                                sequence: other.to_string(),
-                            });
+                        if policy != EscapePolicy::Unescape {
+                            result.push('\\');
                         }
-                        result.push('\\');
                         result.push(other);
                     }
                     None => {
-                        if self.options.strict {
-                            return Err(DecodeError::ParseError(
-                                "Unterminated escape sequence".to_string(),
-                            ));
+                        if policy == EscapePolicy::Strict {
+                            self.report(DecodeError::ParseError {
+                                line: line_num,
+                                message: "Unterminated escape sequence".to_string(),
+                            })?;
+                        }
+                        if policy != EscapePolicy::Unescape {
+                            result.push('\\');
                         }
-                        result.push('\\');
                     }
                 }
             } else {
@@ -671,6 +2110,60 @@ impl<'a> Decoder<'a> {
         Ok(Cow::Owned(result))
     }
 
+    /// Decode the hex digits of a `\uXXXX` escape whose `\u` has been consumed
+    ///
+    /// A high surrogate must be followed by a `\uXXXX` low surrogate, and the
+    /// pair decodes to one astral-plane char. On failure nothing is consumed
+    /// and the malformed sequence (without its leading backslash) is returned.
+    fn decode_unicode_escape(chars: &mut core::str::Chars) -> Result<char, String> {
+        fn hex4(chars: &core::str::Chars) -> Option<u32> {
+            let hex: String = chars.clone().take(4).collect();
+            if hex.len() == 4 && hex.chars().all(|c| c.is_ascii_hexdigit()) {
+                u32::from_str_radix(&hex, 16).ok()
+            } else {
+                None
+            }
+        }
+
+        let malformed = |chars: &core::str::Chars, len: usize| {
+            let rest: String = chars.clone().take(len).collect();
+            Err(format!("u{}", rest))
+        };
+
+        let Some(high) = hex4(chars) else {
+            return malformed(chars, 4);
+        };
+        let code = match high {
+            0xD800..=0xDBFF => {
+                let mut rest = chars.clone();
+                rest.nth(3);
+                let low = match (rest.next(), rest.next()) {
+                    (Some('\\'), Some('u')) => hex4(&rest),
+                    _ => None,
+                };
+                match low {
+                    Some(low @ 0xDC00..=0xDFFF) => {
+                        chars.nth(9);
+                        return Ok(char::from_u32(
+                            0x10000 + ((high - 0xD800) << 10) + (low - 0xDC00),
+                        )
+                        .expect("surrogate pair is a valid scalar value"));
+                    }
+                    _ => return malformed(chars, 4),
+                }
+            }
+            code => code,
+        };
+        match char::from_u32(code) {
+            Some(c) => {
+                chars.nth(3);
+                Ok(c)
+            }
+            // Lone low surrogate
+            None => malformed(chars, 4),
+        }
+    }
+
     /// Unescape string (remove quotes and handle escapes)
     /// Legacy wrapper for backward compatibility
     fn unescape_string(&self, s: &str, line_num: usize) -> Result<String, DecodeError> {
@@ -756,6 +2249,484 @@ mod tests {
         assert_eq!(original, decoded);
     }
 
+    #[test]
+    fn test_semicolon_delimiter_round_trip() {
+        let original = json!({
+            "tags": ["a", "b", "c"],
+            "rows": [
+                {"id": 1, "note": "x,y"},
+                {"id": 2, "note": "p;q"}
+            ]
+        });
+        let options = EncoderOptions {
+            indent: IndentStyle::Spaces(2),
+            delimiter: Delimiter::Semicolon,
+            ..Default::default()
+        };
+
+        let toon = encode(&original, &options);
+        assert!(toon.starts_with("tags[3;]: a;b;c"));
+        let decoded = decode(&toon, &DecoderOptions::default()).unwrap();
+        assert_eq!(original, decoded);
+    }
+
+    #[test]
+    fn test_round_trip_list_item_nested_first_field() {
+        let original = json!({
+            "items": [
+                {"a": {"b": 1, "c": {"d": 2}}, "x": 1},
+                {"y": {"z": true}, "w": "v"},
+                5
+            ]
+        });
+
+        let toon = encode(&original, &EncoderOptions::default());
+        let decoded = decode(&toon, &DecoderOptions::default()).unwrap();
+        assert_eq!(original, decoded);
+    }
+
+    #[test]
+    fn test_round_trip_empty_arrays() {
+        for original in [
+            json!([]),
+            json!([[]]),
+            json!({"x": []}),
+            json!({"x": [[], [1]]}),
+        ] {
+            let toon = encode(&original, &EncoderOptions::default());
+            let decoded = decode(&toon, &DecoderOptions::default()).unwrap();
+            assert_eq!(original, decoded, "round trip failed for {}", toon);
+        }
+        assert_eq!(
+            decode("[0]:", &DecoderOptions::default()).unwrap(),
+            Value::Array(vec![])
+        );
+    }
+
+    #[test]
+    fn test_round_trip_large_integers() {
+        let original = json!({
+            "big": 12345678901234567890u64,
+            "max": u64::MAX,
+            "min": i64::MIN
+        });
+        let toon = encode(&original, &EncoderOptions::default());
+        assert_eq!(
+            toon,
+            "big: 12345678901234567890\nmax: 18446744073709551615\nmin: -9223372036854775808"
+        );
+        let decoded = decode(&toon, &DecoderOptions::default()).unwrap();
+        assert_eq!(original, decoded);
+    }
+
+    #[cfg(feature = "arbitrary_precision")]
+    #[test]
+    fn test_round_trip_integers_beyond_64_bits() {
+        let toon = "big: 123456789012345678901234567890\nneg: -98765432109876543210";
+        let decoded = decode(toon, &DecoderOptions::default()).unwrap();
+        assert_eq!(decoded["big"].to_string(), "123456789012345678901234567890");
+        assert_eq!(encode(&decoded, &EncoderOptions::default()), toon);
+    }
+
+    #[test]
+    fn test_streaming_decoder_matches_decode() {
+        let toon = "name: Test 世界\nitems[2]{id,value}:\n  1,A\n  2,B\n\nnested:\n  list[2]:\n    - x\n    - [2]: 1,2\n";
+        let expected = decode(toon, &DecoderOptions::default()).unwrap();
+
+        for split in 0..=toon.len() {
+            if !toon.is_char_boundary(split) {
+                continue;
+            }
+            let mut decoder = StreamingDecoder::new(DecoderOptions::default());
+            decoder.push_str(&toon[..split]);
+            decoder.push_str(&toon[split..]);
+            assert_eq!(decoder.finish().unwrap(), expected, "split at {}", split);
+        }
+
+        let mut decoder = StreamingDecoder::new(DecoderOptions::default());
+        for ch in toon.chars() {
+            decoder.push_str(ch.encode_utf8(&mut [0; 4]));
+        }
+        assert_eq!(decoder.finish().unwrap(), expected);
+    }
+
+    #[test]
+    fn test_streaming_decoder_reports_errors() {
+        let mut decoder = StreamingDecoder::new(DecoderOptions::default());
+        decoder.push_str("user:\n id");
+        decoder.push_str(": 1\n");
+        assert!(matches!(
+            decoder.finish(),
+            Err(DecodeError::InvalidIndentation { line: 2 })
+        ));
+    }
+
+    #[test]
+    fn test_decode_trailing_newline() {
+        let original = json!({"user": {"id": 1}, "tags": ["a", "b"]});
+        let options = EncoderOptions {
+            trailing_newline: true,
+            ..Default::default()
+        };
+        let toon = encode(&original, &options);
+        assert!(toon.ends_with('\n'));
+        assert_eq!(decode(&toon, &DecoderOptions::default()).unwrap(), original);
+    }
+
+    #[test]
+    fn test_duplicate_key_strict() {
+        let result = decode("a: 1\nb: 2\na: 3", &DecoderOptions::default());
+        assert_eq!(
+            result,
+            Err(DecodeError::DuplicateKey {
+                line: 3,
+                key: "a".to_string()
+            })
+        );
+
+        let result = decode("items[1]:\n  - id: 1\n  id: 2", &DecoderOptions::default());
+        assert!(matches!(
+            result,
+            Err(DecodeError::DuplicateKey { line: 3, .. })
+        ));
+    }
+
+    #[test]
+    fn test_duplicate_key_lenient_last_wins() {
+        let result = decode("a: 1\nb: 2\na: 3", &DecoderOptions::lenient()).unwrap();
+        assert_eq!(result, json!({"a": 3, "b": 2}));
+
+        let result = decode("items[1]:\n  - id: 1\n  id: 2", &DecoderOptions::lenient()).unwrap();
+        assert_eq!(result, json!({"items": [{"id": 2}]}));
+    }
+
+    #[test]
+    fn test_round_trip_quote_all_strings() {
+        let original = json!({
+            "name": "Alice",
+            "rows": [{"id": 1, "tag": "x"}, {"id": 2, "tag": "true"}],
+            "list": ["a", "b"]
+        });
+        let options = EncoderOptions {
+            quote_all_strings: true,
+            ..Default::default()
+        };
+        let toon = encode(&original, &options);
+        assert_eq!(decode(&toon, &DecoderOptions::default()).unwrap(), original);
+    }
+
+    #[test]
+    fn test_round_trip_multiline_strings() {
+        let original = json!({
+            "doc": "first line\nsecond line\nthird line",
+            "pipe": "|",
+            "items": [
+                {"text": "a\nb", "other": "c\nd"},
+                {"text": "x"}
+            ],
+            "nested": {"sql": "SELECT 1\nFROM t"},
+            "md": "# Title\nbody"
+        });
+        let options = EncoderOptions {
+            multiline_strings: true,
+            ..Default::default()
+        };
+        let toon = encode(&original, &options);
+        assert!(toon.starts_with("doc: |\n  first line\n  second line\n  third line\n"));
+        assert!(toon.ends_with("md: \"# Title\\nbody\""));
+        assert_eq!(decode(&toon, &DecoderOptions::default()).unwrap(), original);
+        let with_comments = DecoderOptions {
+            allow_comments: true,
+            ..Default::default()
+        };
+        assert_eq!(decode(&toon, &with_comments).unwrap(), original);
+    }
+
+    #[test]
+    fn test_round_trip_tab_indentation() {
+        let original = json!({
+            "user": {"name": "Bob", "meta": {"level": 3}},
+            "rows": [{"id": 1, "v": ""}, {"id": 2, "v": "x y"}],
+            "items": [{"a": {"b": 1}, "c": 2}, "plain"]
+        });
+        let decode_options = DecoderOptions {
+            indent: IndentStyle::Tabs,
+            ..Default::default()
+        };
+
+        for delimiter in [Delimiter::Comma, Delimiter::Tab] {
+            let options = EncoderOptions {
+                indent: IndentStyle::Tabs,
+                delimiter,
+                ..Default::default()
+            };
+            let toon = encode(&original, &options);
+            assert_eq!(decode(&toon, &decode_options).unwrap(), original);
+        }
+    }
+
+    #[test]
+    fn test_space_indentation_rejects_tabs() {
+        let result = decode("user:\n  name: Alice\n\tid: 1", &DecoderOptions::default());
+        assert_eq!(result, Err(DecodeError::MixedIndentation { line: 3 }));
+
+        let result = decode("user:\n \tid: 1", &DecoderOptions::default());
+        assert_eq!(result, Err(DecodeError::MixedIndentation { line: 2 }));
+    }
+
+    #[test]
+    fn test_tab_indentation_rejects_spaces() {
+        let options = DecoderOptions {
+            indent: IndentStyle::Tabs,
+            ..Default::default()
+        };
+        let result = decode("user:\n\t id: 1", &options);
+        assert_eq!(result, Err(DecodeError::InvalidIndentation { line: 2 }));
+    }
+
+    #[test]
+    fn test_round_trip_aligned_columns() {
+        let original = json!({
+            "users": [
+                {"id": 1, "name": "Alice Smith", "note": "", "score": 9.5},
+                {"id": 1000, "name": "Bo", "note": " padded ", "score": null},
+                {"id": 22, "name": "世界", "note": "x,y", "score": true}
+            ]
+        });
+        let options = EncoderOptions {
+            align_columns: true,
+            ..Default::default()
+        };
+        let toon = encode(&original, &options);
+        assert_ne!(toon, encode(&original, &EncoderOptions::default()));
+        assert_eq!(decode(&toon, &DecoderOptions::default()).unwrap(), original);
+    }
+
+    #[test]
+    fn test_numeric_grammar() {
+        let toon = "values[12]: inf,NaN,1e3,1_000,2.5E-3,.5,5.,007,-05,-0,0.5,1E+2";
+        let result = decode(toon, &DecoderOptions::default()).unwrap();
+        assert_eq!(
+            result,
+            json!({
+                "values": ["inf", "NaN", 1000.0, "1_000", 0.0025, ".5", "5.", "007", "-05", 0, 0.5, 100.0]
+            })
+        );
+
+        let original = json!({
+            "values": ["inf", "NaN", "Infinity", "1e3", "1_000", ".5", "5.", "007", "+5"]
+        });
+        let toon = encode(&original, &EncoderOptions::default());
+        assert_eq!(decode(&toon, &DecoderOptions::default()).unwrap(), original);
+    }
+
+    #[test]
+    fn test_plus_prefixed_numbers_are_strings() {
+        let opts = DecoderOptions::default();
+        assert_eq!(decode("v: +5", &opts).unwrap(), json!({"v": "+5"}));
+        assert_eq!(
+            decode("v[3]: +5,+0.5,+1e3", &opts).unwrap(),
+            json!({"v": ["+5", "+0.5", "+1e3"]})
+        );
+        assert_eq!(
+            decode("rows[1]{n}:\n  +7", &opts).unwrap(),
+            json!({"rows": [{"n": "+7"}]})
+        );
+        assert_eq!(decode("+5", &opts).unwrap(), json!("+5"));
+    }
+
+    #[test]
+    fn test_round_trip_dotted_keys() {
+        let original = json!({
+            "user.name": "Alice",
+            "a.b.c": {"x.y": 1},
+            "1.5": "numeric-looking",
+            ".hidden": true,
+            "rows": [{"id.x": 1, "2.0": "a"}, {"id.x": 2, "2.0": "b"}]
+        });
+        let toon = encode(&original, &EncoderOptions::default());
+        assert_eq!(
+            toon,
+            "user.name: Alice\na.b.c:\n  x.y: 1\n\"1.5\": numeric-looking\n\".hidden\": true\n\
+             rows[2]{id.x,\"2.0\"}:\n  1,a\n  2,b"
+        );
+        assert_eq!(decode(&toon, &DecoderOptions::default()).unwrap(), original);
+    }
+
+    #[test]
+    fn test_round_trip_root_arrays() {
+        let cases = [
+            (json!([1, 2, 3]), "[3]: 1,2,3"),
+            (
+                json!([{"id": 1, "name": "A"}, {"id": 2, "name": "B"}]),
+                "[2]{id,name}:\n  1,A\n  2,B",
+            ),
+            (json!([1, "a"]), "[2]:\n  - 1\n  - a"),
+            (json!([[1], [2]]), "[2]:\n  - [1]: 1\n  - [1]: 2"),
+            (
+                json!([[{"id": 1}], [[1, 2]]]),
+                "[2]:\n  - [1]{id}:\n    1\n  - [1]:\n    - [2]: 1,2",
+            ),
+            (
+                json!([{"a": 1}, {"b": [1, 2]}]),
+                "[2]:\n  - a: 1\n  - b[2]: 1,2",
+            ),
+            (
+                json!([[1], {}, [true]]),
+                "[3]:\n  - [1]: 1\n  -\n  - [1]: true",
+            ),
+            (json!([]), "[0]:"),
+        ];
+        for (value, expected) in cases {
+            let toon = encode(&value, &EncoderOptions::default());
+            assert_eq!(toon, expected);
+            assert_eq!(
+                decode(&toon, &DecoderOptions::default()).unwrap(),
+                value,
+                "{}",
+                toon
+            );
+        }
+
+        let options = EncoderOptions {
+            delimiter: Delimiter::Pipe,
+            ..Default::default()
+        };
+        let value = json!([{"id": 1, "tags": "a,b"}, {"id": 2, "tags": "c"}]);
+        let toon = encode(&value, &options);
+        assert_eq!(toon, "[2|]{id|tags}:\n  1|a,b\n  2|c");
+        assert_eq!(decode(&toon, &DecoderOptions::default()).unwrap(), value);
+    }
+
+    #[test]
+    fn test_decode_keys_with_escaped_quotes() {
+        let input = "\"a\\\"b\": 1\n\"c\\\\\": 2\n\"d\\\":e\": 3";
+        let result = decode(input, &DecoderOptions::default()).unwrap();
+        assert_eq!(result, json!({"a\"b": 1, "c\\": 2, "d\":e": 3}));
+    }
+
+    #[test]
+    fn test_decode_quoted_field_names() {
+        let toon = "rows[2]{\"weird}name\",\"a,b\",\"c:d\"}:\n  1,2,3\n  4,5,6";
+        let result = decode(toon, &DecoderOptions::default()).unwrap();
+        let expected = json!({
+            "rows": [
+                {"weird}name": 1, "a,b": 2, "c:d": 3},
+                {"weird}name": 4, "a,b": 5, "c:d": 6}
+            ]
+        });
+        assert_eq!(result, expected);
+
+        let toon = encode(&expected, &EncoderOptions::default());
+        assert_eq!(decode(&toon, &DecoderOptions::default()).unwrap(), expected);
+    }
+
+    #[test]
+    fn test_decode_scientific_notation() {
+        let toon = "x: 6.022e23\ny: -1.5E-7\nz: 1e+2";
+        let result = decode(toon, &DecoderOptions::default()).unwrap();
+        assert_eq!(result["x"].as_f64(), Some(6.022e23));
+        assert_eq!(result["y"].as_f64(), Some(-1.5e-7));
+        assert_eq!(result["z"].as_f64(), Some(100.0));
+    }
+
+    #[test]
+    fn test_decode_with_trace() {
+        let input =
+            "users[2]{id,name}:\n  1,Alice\n  2,Bob\nmeta:\n  tags[2]: a,b\n  a~b[1]:\n    - [0]:";
+        let (value, trace) = decode_with_trace(input, &DecoderOptions::default()).unwrap();
+        assert_eq!(value, decode(input, &DecoderOptions::default()).unwrap());
+
+        assert_eq!(trace.get("/users"), Some(ArrayForm::Tabular));
+        assert_eq!(trace.get("/meta/tags"), Some(ArrayForm::Inline));
+        assert_eq!(trace.get("/meta/a~0b"), Some(ArrayForm::List));
+        assert_eq!(trace.get("/meta/a~0b/0"), Some(ArrayForm::List));
+        assert_eq!(trace.get("/meta"), None);
+        assert_eq!(trace.iter().count(), 4);
+
+        let (_, trace) =
+            decode_with_trace("[2]:\n  - a\n  - b", &DecoderOptions::default()).unwrap();
+        assert_eq!(trace.get(""), Some(ArrayForm::List));
+    }
+
+    #[test]
+    fn test_decode_unicode_escapes() {
+        let opts = DecoderOptions::default();
+        assert_eq!(
+            decode(r#"text: "\u0041\u00e9\u4E2D""#, &opts).unwrap(),
+            json!({"text": "Aé中"})
+        );
+        assert_eq!(
+            decode(r#"text: "hi \uD83D\uDE00!""#, &opts).unwrap(),
+            json!({"text": "hi 😀!"})
+        );
+
+        for input in [
+            r#"text: "\u00g1""#,
+            r#"text: "\u12""#,
+            r#"text: "\uD83D""#,
+            r#"text: "\uD83Dx""#,
+            r#"text: "\uDE00""#,
+        ] {
+            assert!(
+                matches!(
+                    decode(input, &opts),
+                    Err(DecodeError::InvalidEscapeSequence { .. })
+                ),
+                "{}",
+                input
+            );
+        }
+
+        // Lenient mode keeps malformed escapes verbatim
+        assert_eq!(
+            decode(r#"text: "\uD83D!""#, &DecoderOptions::lenient()).unwrap(),
+            json!({"text": "\\uD83D!"})
+        );
+    }
+
+    #[test]
+    fn test_bracketed_text_is_not_array_header() {
+        let opts = DecoderOptions::default();
+        assert_eq!(
+            decode(r#""[draft]: notes""#, &opts).unwrap(),
+            json!("[draft]: notes")
+        );
+        assert_eq!(
+            decode("[draft]: notes", &opts).unwrap(),
+            json!({"[draft]": "notes"})
+        );
+        assert_eq!(
+            decode("items[2]:\n  - [draft]: notes\n  - [|]: a|b", &opts).unwrap(),
+            json!({"items": [{"[draft]": "notes"}, ["a", "b"]]})
+        );
+        assert_eq!(
+            decode("[2]{id}:\n  1\n  2", &opts).unwrap(),
+            json!([{"id": 1}, {"id": 2}])
+        );
+    }
+
+    #[test]
+    fn test_split_escaped_quotes_and_backslashes() {
+        let opts = DecoderOptions::default();
+        assert_eq!(
+            decode(r#"v[2]: "a\\",b"#, &opts).unwrap(),
+            json!({"v": ["a\\", "b"]})
+        );
+        assert_eq!(
+            decode(r#"v[2]: "a\"b",c"#, &opts).unwrap(),
+            json!({"v": ["a\"b", "c"]})
+        );
+        assert_eq!(
+            decode(r#"v[3]: "\\\"",",\\",x"#, &opts).unwrap(),
+            json!({"v": ["\\\"", ",\\", "x"]})
+        );
+        assert_eq!(
+            decode("rows[1]{a,b}:\n  \"a\\\\\",\"b\\\"\"", &opts).unwrap(),
+            json!({"rows": [{"a": "a\\", "b": "b\""}]})
+        );
+    }
+
     #[test]
     fn test_decode_mixed_array() {
         let toon = "items[3]:\n  - 42\n  - text\n  - true";
@@ -787,6 +2758,7 @@ mod tests {
         assert!(matches!(
             result,
             Err(DecodeError::ArrayLengthMismatch {
+                line: 1,
                 expected: 2,
                 found: 3
             })
@@ -1,48 +1,641 @@
 //! JSON to TOON encoder implementation
 
-use crate::common::Delimiter;
+use crate::common::{is_integer_literal, is_numeric_literal, Delimiter, IndentStyle, SpecVersion};
+use alloc::borrow::Cow;
+use alloc::collections::BTreeSet;
+use alloc::format;
+use alloc::string::{String, ToString};
+use alloc::vec;
+use alloc::vec::Vec;
+use core::fmt::{self, Write};
 use serde_json::Value;
+#[cfg(feature = "std")]
+use {crate::error::EncodeError, std::io};
 
 /// Encoder configuration options
 #[derive(Debug, Clone)]
 pub struct EncoderOptions {
-    /// Spaces per indentation level (default: 2)
-    pub indent: usize,
+    /// Indentation per nesting level (default: 2 spaces)
+    pub indent: IndentStyle,
     /// Document-wide delimiter for quoting decisions (default: Comma)
     pub delimiter: Delimiter,
+    /// Emit object keys and tabular fields in lexicographic order (default: false)
+    pub sort_keys: bool,
+    /// Terminate non-empty documents with a single `\n` (default: false)
+    pub trailing_newline: bool,
+    /// Quote every string value, including tabular cells (default: false)
+    ///
+    /// Keys keep their normal quoting rules.
+    pub quote_all_strings: bool,
+    /// Emit multi-line string field values as `|` blocks (default: false)
+    ///
+    /// A string is written as an indented block under its key when it contains
+    /// `\n` and every line is non-empty, free of control characters, does not
+    /// start with `#`, and has no leading or trailing whitespace. Other strings keep the escaped,
+    /// single-line form. Tabular cells and inline array values are never blocks.
+    pub multiline_strings: bool,
+    /// Pad tabular cells with spaces so columns line up (default: false)
+    ///
+    /// Each cell except the last in a row is padded to the widest value in
+    /// its column. The decoder trims cells, so aligned output round-trips.
+    pub align_columns: bool,
+    /// Choose the delimiter per array to minimize quoting (default: false)
+    ///
+    /// Each tabular or inline array uses whichever of `delimiter`, comma, pipe
+    /// and tab appears in the fewest of its string values, preferring
+    /// `delimiter` on ties. The choice is recorded in the array header, e.g.
+    /// `[3|]`, so decoders recover it. Other values still quote against
+    /// `delimiter`.
+    pub delimiter_auto: bool,
+    /// Keep scientific notation for floats that format that way (default: false)
+    ///
+    /// By default `6.022e23` is expanded to plain decimal digits; with this
+    /// set it is emitted as `6.022e+23`. Decoders accept both forms.
+    pub preserve_scientific: bool,
+    /// Write the item count in array headers, e.g. `[3]` (default: true)
+    ///
+    /// When false, headers are written as `[]`, `[|]` and so on, and decoders
+    /// skip the length check for those arrays.
+    pub emit_lengths: bool,
+    /// Maximum line width for inline primitive arrays (default: None)
+    ///
+    /// An inline array whose full line (indentation, key, header and values)
+    /// would exceed this many characters is written in the expanded `- item`
+    /// list form instead. `None` never wraps.
+    pub line_width: Option<usize>,
+    /// Format floats exactly like JavaScript's `Number.prototype.toString` (default: false)
+    ///
+    /// Uses the shortest round-trip digits, with exponent notation for
+    /// magnitudes of at least 1e21 or below 1e-6 (e.g. `1e+21`, `1.5e-7`), so
+    /// output matches `JSON.stringify`. Takes precedence over
+    /// `preserve_scientific`. Integers are always written exactly.
+    pub js_compatible_numbers: bool,
+    /// Minimum number of rows for an array to use tabular form (default: 1)
+    ///
+    /// Eligible arrays with fewer rows use the list form instead. The default
+    /// makes every eligible array tabular.
+    pub tabular_threshold: usize,
+    /// TOON specification version to emit (default: `SpecVersion::V2_0`)
+    pub spec_version: SpecVersion,
+    /// Escape every non-ASCII character as `\uXXXX` (default: false)
+    ///
+    /// Strings and keys containing non-ASCII characters are quoted, and
+    /// characters outside the Basic Multilingual Plane become a surrogate
+    /// pair, so the output is pure ASCII.
+    pub escape_unicode: bool,
+    /// Omit object fields whose value is `null` (default: false)
+    ///
+    /// Skipped fields do not count towards tabular detection: objects whose
+    /// `null` fields differ no longer share the same keys and fall back to the
+    /// list form, while a field that is `null` in every object is dropped from
+    /// the tabular header.
+    pub skip_nulls: bool,
+    /// Rewrite object keys and tabular fields in the given case (default: None)
+    ///
+    /// This is lossy: decoding yields the rewritten keys, not the originals,
+    /// and distinct keys such as `user_name` and `userName` may collide.
+    /// Kebab-case keys contain `-` and are therefore quoted.
+    pub key_case: Option<KeyCase>,
+    /// Write a space between a `:` and the value that follows it (default: true)
+    ///
+    /// Turning this off gives `name:Alice` and `tags[3]:a,b,c`, which the
+    /// decoder accepts as well.
+    pub space_after_colon: bool,
+    /// Keys whose string values carry base64 data (default: empty)
+    ///
+    /// A value of a listed key, whether an object field or a tabular cell, is
+    /// checked against the standard base64 alphabet with `=` padding. Valid
+    /// values are written verbatim and unquoted, even with
+    /// `quote_all_strings` or `multiline_strings` set, unless they would read
+    /// back as a number or keyword (e.g. `1234` or `null`). Other values are
+    /// encoded like any string.
+    pub base64_keys: BTreeSet<String>,
+    /// Maximum element count for inline primitive arrays (default: None)
+    ///
+    /// A primitive array with more elements is written in the expanded
+    /// `- item` list form, whatever its width. Like `line_width`, this does
+    /// not apply to primitive arrays that are themselves list items, which
+    /// are always inline. `None` never expands.
+    pub max_inline_array_len: Option<usize>,
+    /// Keys to write first, in this order (default: None)
+    ///
+    /// Listed keys present in an object come first in the given sequence; the
+    /// remaining keys follow in their usual order, which is sorted when
+    /// `sort_keys` is set. Tabular headers and row cells are reordered alike.
+    pub key_order: Option<Vec<String>>,
+    /// Use the union of keys as the columns of tabular arrays (default: false)
+    ///
+    /// Arrays of objects with primitive values then use tabular form even when
+    /// some objects lack some keys. A missing field is written as a `null`
+    /// cell rather than an empty one, which would decode as an empty string
+    /// and, with tab delimiters, could be taken for indentation. Decoding
+    /// therefore yields `null` for fields that were absent. Objects without
+    /// any field keep the array in list form.
+    pub tabular_union_keys: bool,
+    /// Write nested objects with fewer than this many fields on one line (default: None)
+    ///
+    /// A non-empty object field whose values are all primitives is written as
+    /// `key: {a: 1, b: x}` instead of an indented block. Values are quoted as
+    /// in a comma-delimited array, so they never contain a bare `,` or `}`.
+    /// This is an extension to TOON; the decoder reads it back with
+    /// `DecoderOptions::inline_objects`. Array items and the root keep the
+    /// usual forms. `None` never inlines.
+    pub inline_small_objects: Option<usize>,
+    /// Start the item of a one-element list-form array on the header line (default: false)
+    ///
+    /// Arrays whose single item cannot be tabular or inline, such as an
+    /// object holding a nested array, are written as `items[1]: - a: 1`
+    /// instead of putting `- a: 1` on its own line. The rest of the item
+    /// follows on the next lines exactly as in the usual list form:
+    ///
+    /// ```text
+    /// items[1]: - a: 1
+    ///   b[2]: 1,2
+    /// ```
+    ///
+    /// This is an extension to TOON; the decoder reads it back with
+    /// `DecoderOptions::same_line_list_items`.
+    pub array_on_same_line: bool,
+    /// Write ISO-8601 timestamps such as `2025-01-01T10:00:00Z` unquoted (default: false)
+    ///
+    /// Their `:` would otherwise force quotes. Applies to object field values
+    /// and to inline and tabular array cells, where the decoder reads them
+    /// back as strings; list items and a root string stay quoted, since there
+    /// the first `:` would split a key. Only complete date-times with seconds
+    /// are recognized: `YYYY-MM-DDTHH:MM:SS`, optionally with a `.fraction`
+    /// and a `Z` or `+HH:MM` offset.
+    pub unquote_iso_dates: bool,
+    /// Keys whose values are replaced by a redaction marker (default: empty)
+    ///
+    /// The value of a field with one of these keys, at any depth and in
+    /// tabular rows, is written as the quoted string `"***"` whatever it
+    /// holds, so nested objects and arrays are hidden too. Keys are matched
+    /// before `key_case` is applied. Intended for logging data that contains
+    /// secrets such as passwords or tokens.
+    pub redact_keys: BTreeSet<String>,
+}
+
+/// Written in place of the values of [`EncoderOptions::redact_keys`]
+const REDACTED: &str = "\"***\"";
+
+/// Casing applied to keys by [`EncoderOptions::key_case`]
+///
+/// Words are split at `_`, `-` and spaces and at case changes, so
+/// `user_name`, `userName`, `UserName` and `user-name` all have the words
+/// `user` and `name`. An acronym followed by a word (`HTTPServer`) splits
+/// before the word's capital.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum KeyCase {
+    /// `userName`
+    Camel,
+    /// `user_name`
+    Snake,
+    /// `user-name`
+    Kebab,
+}
+
+impl KeyCase {
+    /// Rewrite `key` in this case; keys with no words are returned unchanged
+    pub fn apply(&self, key: &str) -> String {
+        let words = Self::words(key);
+        if words.is_empty() {
+            return key.to_string();
+        }
+        match self {
+            KeyCase::Camel => {
+                let mut result = words[0].to_lowercase();
+                for word in &words[1..] {
+                    let mut chars = word.chars();
+                    if let Some(first) = chars.next() {
+                        result.extend(first.to_uppercase());
+                        result.push_str(&chars.as_str().to_lowercase());
+                    }
+                }
+                result
+            }
+            KeyCase::Snake => words.join("_").to_lowercase(),
+            KeyCase::Kebab => words.join("-").to_lowercase(),
+        }
+    }
+
+    /// Split a key into words at separators and case changes
+    fn words(key: &str) -> Vec<&str> {
+        let mut words = Vec::new();
+        for part in key.split(['_', '-', ' ']).filter(|p| !p.is_empty()) {
+            let chars: Vec<(usize, char)> = part.char_indices().collect();
+            let mut start = 0;
+            for i in 1..chars.len() {
+                let (prev, cur) = (chars[i - 1].1, chars[i].1);
+                let next_lower = chars.get(i + 1).is_some_and(|(_, c)| c.is_lowercase());
+                let boundary = cur.is_uppercase()
+                    && (prev.is_lowercase()
+                        || prev.is_ascii_digit()
+                        || (prev.is_uppercase() && next_lower));
+                if boundary {
+                    words.push(&part[start..chars[i].0]);
+                    start = chars[i].0;
+                }
+            }
+            words.push(&part[start..]);
+        }
+        words
+    }
 }
 
 impl Default for EncoderOptions {
     fn default() -> Self {
         Self {
-            indent: 2,
+            indent: IndentStyle::default(),
+            delimiter: Delimiter::Comma,
+            sort_keys: false,
+            trailing_newline: false,
+            quote_all_strings: false,
+            multiline_strings: false,
+            align_columns: false,
+            delimiter_auto: false,
+            preserve_scientific: false,
+            emit_lengths: true,
+            line_width: None,
+            js_compatible_numbers: false,
+            tabular_threshold: 1,
+            spec_version: SpecVersion::default(),
+            escape_unicode: false,
+            skip_nulls: false,
+            key_case: None,
+            space_after_colon: true,
+            base64_keys: BTreeSet::new(),
+            max_inline_array_len: None,
+            key_order: None,
+            tabular_union_keys: false,
+            inline_small_objects: None,
+            array_on_same_line: false,
+            unquote_iso_dates: false,
+            redact_keys: BTreeSet::new(),
+        }
+    }
+}
+
+impl EncoderOptions {
+    /// Compact preset for token-sensitive contexts such as LLM prompts
+    ///
+    /// Compared to [`EncoderOptions::default`], this indents by a single
+    /// space per level. It also pins the settings that keep output small:
+    /// - comma delimiter, without `delimiter_auto`
+    /// - tabular form for every eligible array (`tabular_threshold` of 1)
+    /// - array lengths emitted, since they help readers check structure
+    /// - no column alignment, no `|` blocks, no trailing newline
+    /// - strings quoted only when required, numbers without exponents
+    ///
+    /// Decode the output with `IndentStyle::Spaces(1)` or the lenient preset.
+    pub fn compact() -> Self {
+        Self {
+            indent: IndentStyle::Spaces(1),
             delimiter: Delimiter::Comma,
+            delimiter_auto: false,
+            tabular_threshold: 1,
+            emit_lengths: true,
+            align_columns: false,
+            multiline_strings: false,
+            trailing_newline: false,
+            quote_all_strings: false,
+            preserve_scientific: false,
+            ..Self::default()
+        }
+    }
+
+    /// Check the options for combinations that cannot produce decodable output
+    ///
+    /// `IndentStyle::Spaces(0)` is rejected, since nested values would then sit
+    /// at the same depth as their parent. Encoding with invalid options panics
+    /// in debug builds once nested output is written.
+    pub fn validate(&self) -> Result<(), String> {
+        if self.indent == IndentStyle::Spaces(0) {
+            return Err("indent must be at least 1 space".to_string());
         }
+        Ok(())
     }
 }
 
 /// Encode a JSON value to TOON format
 pub fn encode(value: &Value, options: &EncoderOptions) -> String {
-    let mut encoder = Encoder::new(options);
-    encoder.encode_value(value, 0);
-    encoder.output
+    let mut out = String::new();
+    encode_into(value, options, &mut out).expect("writing to a String cannot fail");
+    out
+}
+
+/// Encode a JSON value into any `fmt::Write` sink
+fn encode_into<W: Write>(value: &Value, options: &EncoderOptions, out: W) -> fmt::Result {
+    let mut encoder = Encoder::new(options, out);
+    encoder.encode_value(value, 0)?;
+    encoder.finish()
+}
+
+/// Encode a JSON value to TOON format, borrowing the output when possible
+///
+/// Root `null`, booleans, empty objects and strings that need no quoting are
+/// returned as [`Cow::Borrowed`] without allocating, which helps when encoding
+/// many small values. Everything else, and any value when `trailing_newline`
+/// is set, is encoded as by [`encode`].
+///
+/// ```rust
+/// use json2toon_rs::{encode_cow, EncoderOptions};
+/// use serde_json::json;
+/// use std::borrow::Cow;
+///
+/// let value = json!("hello");
+/// assert!(matches!(encode_cow(&value, &EncoderOptions::default()), Cow::Borrowed("hello")));
+/// ```
+pub fn encode_cow<'v>(value: &'v Value, options: &EncoderOptions) -> Cow<'v, str> {
+    if options.trailing_newline && !matches!(value, Value::Object(obj) if obj.is_empty()) {
+        return Cow::Owned(encode(value, options));
+    }
+    match value {
+        Value::Null => Cow::Borrowed("null"),
+        Value::Bool(true) => Cow::Borrowed("true"),
+        Value::Bool(false) => Cow::Borrowed("false"),
+        Value::Object(obj) if obj.is_empty() => Cow::Borrowed(""),
+        Value::String(s) => {
+            let encoder = Encoder::new(options, String::new());
+            if encoder.needs_quoting(s, options.delimiter) {
+                Cow::Owned(encoder.quote_and_escape(s))
+            } else {
+                Cow::Borrowed(s)
+            }
+        }
+        _ => Cow::Owned(encode(value, options)),
+    }
+}
+
+/// Encode a JSON value to TOON format, streaming the output to `writer`
+///
+/// Output is written incrementally, so large documents never need to be held
+/// in memory as a single `String`. Wrap unbuffered sinks (files, sockets) in a
+/// `std::io::BufWriter`, as the encoder performs many small writes.
+#[cfg(feature = "std")]
+pub fn encode_to_writer<W: io::Write>(
+    value: &Value,
+    options: &EncoderOptions,
+    writer: &mut W,
+) -> io::Result<()> {
+    let mut out = IoWriter::new(writer);
+    encode_into(value, options, &mut out)
+        .map_err(|err| out.take_error().unwrap_or_else(|| io::Error::other(err)))
+}
+
+/// Encode rows pulled from an iterator as a root tabular array, streaming to `writer`
+///
+/// Each row is written as soon as it is produced, so the rows never need to
+/// be held in memory together. Since the row count is not known up front, the
+/// header omits the length, as with `emit_lengths` off: `[]{id,name}:`.
+/// Cells are taken from each object in `fields` order, and a missing field is
+/// written as `null`. A row that is not an object, or a cell that is an object
+/// or array, is an [`EncodeError::Serialize`], as is an empty `fields`; rows
+/// before it have already been written.
+///
+/// ```rust
+/// use json2toon_rs::{encode_array_streaming, Delimiter};
+/// use serde_json::json;
+///
+/// let rows = (1..=2).map(|id| json!({"id": id, "name": format!("user{}", id)}));
+/// let fields = ["id".to_string(), "name".to_string()];
+/// let mut out = Vec::new();
+/// encode_array_streaming(rows, &fields, Delimiter::Comma, &mut out).unwrap();
+/// assert_eq!(String::from_utf8(out).unwrap(), "[]{id,name}:\n  1,user1\n  2,user2");
+/// ```
+#[cfg(feature = "std")]
+pub fn encode_array_streaming<I, W>(
+    rows: I,
+    fields: &[String],
+    delimiter: Delimiter,
+    writer: &mut W,
+) -> Result<(), EncodeError>
+where
+    I: IntoIterator<Item = Value>,
+    W: io::Write,
+{
+    let mut out = IoWriter::new(writer);
+    write_rows(rows, fields, delimiter, &mut out)
+        .map_err(|err| out.take_error().map_or(err, EncodeError::from))
+}
+
+/// Write the tabular array of [`encode_array_streaming`] into a `fmt::Write` sink
+#[cfg(feature = "std")]
+fn write_rows<I, W>(
+    rows: I,
+    fields: &[String],
+    delimiter: Delimiter,
+    writer: W,
+) -> Result<(), EncodeError>
+where
+    I: IntoIterator<Item = Value>,
+    W: Write,
+{
+    if fields.is_empty() {
+        return Err(EncodeError::Serialize(
+            "streamed tabular arrays need at least one field".to_string(),
+        ));
+    }
+
+    let options = EncoderOptions {
+        delimiter,
+        emit_lengths: false,
+        ..EncoderOptions::default()
+    };
+    let mut encoder = Encoder::new(&options, writer);
+    encoder.write_array_header(0, delimiter, Some(fields))?;
+
+    for row in rows {
+        let Value::Object(map) = row else {
+            return Err(EncodeError::Serialize(
+                "streamed tabular rows must be objects".to_string(),
+            ));
+        };
+        encoder.push('\n')?;
+        encoder.indent(1)?;
+        for (i, field) in fields.iter().enumerate() {
+            if i > 0 {
+                encoder.push(delimiter.as_char())?;
+            }
+            let cell = match map.get(field) {
+                Some(value) if !Encoder::<W>::is_primitive(value) => {
+                    return Err(EncodeError::Serialize(format!(
+                        "tabular cell `{}` must be a primitive",
+                        field
+                    )));
+                }
+                Some(value) => encoder.quote_primitive(value, delimiter),
+                None => "null".to_string(),
+            };
+            encoder.push_str(&cell)?;
+        }
+    }
+    Ok(encoder.finish()?)
+}
+
+/// Describe how the encoder lays out each array in a JSON value
+///
+/// Returns one line per array, in document order, of the form
+/// `<path>: <format>: <reason>`, where `<path>` starts at `$` and `<format>`
+/// is `tabular`, `inline` or `list`. Intended for debugging why output looks
+/// the way it does; the wording is not a stable format.
+///
+/// ```rust
+/// use json2toon_rs::{describe, EncoderOptions};
+/// use serde_json::json;
+///
+/// let data = json!({"users": [{"id": 1}, {"id": 2}], "mixed": [1, "a"]});
+/// assert_eq!(
+///     describe(&data, &EncoderOptions::default()),
+///     "$.users: tabular: uniform object keys [id]\n$.mixed: list: mixed primitive types"
+/// );
+/// ```
+pub fn describe(value: &Value, options: &EncoderOptions) -> String {
+    let encoder = Encoder::new(options, String::new());
+    let mut lines = Vec::new();
+    encoder.describe_into(value, "$".to_string(), false, &mut lines);
+    lines.join("\n")
+}
+
+/// A wrapper that formats a JSON value as TOON through `std::fmt::Display`
+///
+/// Output is written straight into the formatter, so `format!`, `println!` and
+/// `write!` produce TOON without an intermediate `String`.
+///
+/// ```rust
+/// use json2toon_rs::{EncoderOptions, ToonDisplay};
+/// use serde_json::json;
+///
+/// let value = json!({"name": "Alice"});
+/// let options = EncoderOptions::default();
+/// assert_eq!(format!("{}", ToonDisplay::new(&value, &options)), "name: Alice");
+/// ```
+#[derive(Debug, Clone, Copy)]
+pub struct ToonDisplay<'a> {
+    value: &'a Value,
+    options: &'a EncoderOptions,
+}
+
+impl<'a> ToonDisplay<'a> {
+    /// Create a display wrapper for `value` using the given encoder options
+    pub fn new(value: &'a Value, options: &'a EncoderOptions) -> Self {
+        Self { value, options }
+    }
+}
+
+impl fmt::Display for ToonDisplay<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        encode_into(self.value, self.options, f)
+    }
+}
+
+/// Extension trait for formatting values as TOON via [`ToonDisplay`]
+///
+/// ```rust
+/// use json2toon_rs::{EncoderOptions, ToToon};
+/// use serde_json::json;
+///
+/// let value = json!({"tags": ["a", "b"]});
+/// println!("{}", value.to_toon(&EncoderOptions::default()));
+/// ```
+pub trait ToToon {
+    /// Returns a wrapper that displays `self` as TOON
+    fn to_toon<'a>(&'a self, options: &'a EncoderOptions) -> ToonDisplay<'a>;
+}
+
+impl ToToon for Value {
+    fn to_toon<'a>(&'a self, options: &'a EncoderOptions) -> ToonDisplay<'a> {
+        ToonDisplay::new(self, options)
+    }
+}
+
+/// Adapts an `io::Write` to `fmt::Write` so the encoder can target it
+///
+/// A failed write surfaces as `fmt::Error`, which carries no detail, so the
+/// underlying I/O error is kept for [`IoWriter::take_error`].
+#[cfg(feature = "std")]
+pub(crate) struct IoWriter<W> {
+    inner: W,
+    error: Option<io::Error>,
+}
+
+#[cfg(feature = "std")]
+impl<W: io::Write> IoWriter<W> {
+    pub(crate) fn new(inner: W) -> Self {
+        Self { inner, error: None }
+    }
+
+    /// The I/O error behind the last failed write, if any
+    pub(crate) fn take_error(&mut self) -> Option<io::Error> {
+        self.error.take()
+    }
+}
+
+#[cfg(feature = "std")]
+impl<W: io::Write> Write for IoWriter<W> {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        self.inner.write_all(s.as_bytes()).map_err(|err| {
+            self.error = Some(err);
+            fmt::Error
+        })
+    }
 }
 
-struct Encoder<'a> {
+pub(crate) struct Encoder<'a, W: Write> {
     options: &'a EncoderOptions,
-    output: String,
+    out: W,
+    /// Whether anything has been written yet (an empty root object writes nothing)
+    has_output: bool,
+    /// Characters written since the last newline
+    column: usize,
+    /// Whether the field whose key was written last is in `base64_keys`
+    base64_field: bool,
+    /// Whether the field whose key was written last is in `redact_keys`
+    redact_field: bool,
 }
 
-impl<'a> Encoder<'a> {
-    fn new(options: &'a EncoderOptions) -> Self {
+impl<'a, W: Write> Encoder<'a, W> {
+    pub(crate) fn new(options: &'a EncoderOptions, out: W) -> Self {
         Self {
             options,
-            output: String::new(),
+            out,
+            has_output: false,
+            column: 0,
+            base64_field: false,
+            redact_field: false,
+        }
+    }
+
+    /// Options this encoder was created with
+    pub(crate) fn options(&self) -> &'a EncoderOptions {
+        self.options
+    }
+
+    /// Complete the document, adding the trailing newline if requested
+    pub(crate) fn finish(&mut self) -> fmt::Result {
+        if self.options.trailing_newline && self.has_output {
+            self.push('\n')?;
+        }
+        Ok(())
+    }
+
+    /// Write a string slice to the output
+    fn push_str(&mut self, s: &str) -> fmt::Result {
+        self.has_output |= !s.is_empty();
+        match s.rfind('\n') {
+            Some(i) => self.column = s[i + 1..].chars().count(),
+            None => self.column += s.chars().count(),
         }
+        self.out.write_str(s)
+    }
+
+    /// Write a single character to the output
+    pub(crate) fn push(&mut self, c: char) -> fmt::Result {
+        let mut buf = [0; 4];
+        self.push_str(c.encode_utf8(&mut buf))
     }
 
     /// Main encoding entry point
-    fn encode_value(&mut self, value: &Value, depth: usize) {
+    pub(crate) fn encode_value(&mut self, value: &Value, depth: usize) -> fmt::Result {
         match value {
             Value::Object(obj) if obj.is_empty() => {
                 // Empty object at root = empty document
@@ -51,175 +644,522 @@ impl<'a> Encoder<'a> {
                 } else {
                     // Empty nested object handled elsewhere
                 }
+                Ok(())
             }
             Value::Object(obj) => self.encode_object(obj, depth),
             Value::Array(arr) => self.encode_array(arr, depth, None),
-            Value::Null => self.output.push_str("null"),
-            Value::Bool(b) => self.output.push_str(&b.to_string()),
-            Value::Number(n) => self.output.push_str(&self.normalize_number(n)),
-            Value::String(s) => self
-                .output
-                .push_str(&self.quote_string(s, self.options.delimiter)),
+            Value::Null => self.push_str("null"),
+            Value::Bool(b) => self.push_str(if *b { "true" } else { "false" }),
+            Value::Number(n) => {
+                let s = self.normalize_number(n);
+                self.push_str(&s)
+            }
+            Value::String(s) => {
+                let quoted = self.quote_string(s, self.options.delimiter);
+                self.push_str(&quoted)
+            }
         }
     }
 
     /// Encode an object
-    fn encode_object(&mut self, obj: &serde_json::Map<String, Value>, depth: usize) {
-        for (i, (key, value)) in obj.iter().enumerate() {
-            if i > 0 {
-                self.output.push('\n');
-            } else if depth > 0 {
-                // First field at non-root depth (don't add newline before first field at root)
-                self.output.push('\n');
+    fn encode_object(&mut self, obj: &serde_json::Map<String, Value>, depth: usize) -> fmt::Result {
+        for (i, (key, value)) in self.entries(obj).into_iter().enumerate() {
+            self.write_field_key(i, key, depth)?;
+            self.encode_field_value(value, depth)?;
+        }
+        Ok(())
+    }
+
+    /// Start the `index`-th field of an object at `depth` by writing its key
+    pub(crate) fn write_field_key(&mut self, index: usize, key: &str, depth: usize) -> fmt::Result {
+        // Every field starts its own line, except the first root field, which
+        // opens the document; nested first fields follow their parent's `key:`
+        if index > 0 || depth > 0 {
+            self.push('\n')?;
+        }
+        self.indent(depth)?;
+        self.base64_field = self.options.base64_keys.contains(key);
+        self.redact_field = self.options.redact_keys.contains(key);
+        let encoded_key = self.encode_key(key);
+        self.push_str(&encoded_key)
+    }
+
+    /// Write the value of a field whose key, at `depth`, was just written
+    pub(crate) fn encode_field_value(&mut self, value: &Value, depth: usize) -> fmt::Result {
+        if self.redact_field {
+            self.push(':')?;
+            self.write_colon_space()?;
+            return self.push_str(REDACTED);
+        }
+        match value {
+            Value::Object(nested) if nested.is_empty() => {
+                // Empty nested object: key:
+                self.push(':')
+            }
+            Value::Object(nested) if self.is_inline_object(nested) => {
+                // Small nested object on one line: key: {a: 1, b: 2}
+                self.push(':')?;
+                self.write_colon_space()?;
+                self.write_inline_object(nested)
+            }
+            Value::Object(nested) => {
+                // Nested object: key:
+                self.push(':')?;
+                // Children will add their own leading newline
+                self.encode_object(nested, depth + 1)
             }
-            self.indent(depth);
-            self.output.push_str(&self.encode_key(key));
+            Value::Array(arr) => {
+                // Array as object field: key[N]:
+                // Don't write colon yet - array header includes it
+                self.encode_array_after_key(arr, depth)
+            }
+            _ => {
+                // Primitive value: key: value
+                self.push(':')?;
+                self.write_field_primitive(value, depth + 1)
+            }
+        }
+    }
 
-            match value {
-                Value::Object(nested) if nested.is_empty() => {
-                    // Empty nested object: key:
-                    self.output.push(':');
-                    continue;
-                }
-                Value::Object(nested) => {
-                    // Nested object: key:
-                    self.output.push(':');
-                    // Children will add their own leading newline
-                    self.encode_object(nested, depth + 1);
-                }
-                Value::Array(arr) => {
-                    // Array as object field: key[N]:
-                    // Don't write colon yet - array header includes it
-                    self.encode_array_after_key(arr, depth);
-                }
-                _ => {
-                    // Primitive value: key: value
-                    self.output.push(':');
-                    self.output.push(' ');
-                    self.encode_primitive(value, self.options.delimiter);
+    /// Write a primitive field value after its `key:`
+    ///
+    /// Multi-line strings become a `|` block whose lines sit at `block_depth`.
+    fn write_field_primitive(&mut self, value: &Value, block_depth: usize) -> fmt::Result {
+        if let Value::String(s) = value {
+            if self.base64_field && self.is_verbatim_base64(s) {
+                self.write_colon_space()?;
+                return self.push_str(s);
+            }
+            if self.options.multiline_strings && Self::is_block_safe(s) {
+                self.write_colon_space()?;
+                self.push('|')?;
+                for line in s.split('\n') {
+                    self.push('\n')?;
+                    self.indent(block_depth)?;
+                    self.push_str(line)?;
                 }
+                return Ok(());
+            }
+        }
+
+        self.write_colon_space()?;
+        let s = self.quote_value(value, self.options.delimiter);
+        self.push_str(&s)
+    }
+
+    /// Check if an object field qualifies for the `inline_small_objects` form
+    fn is_inline_object(&self, obj: &serde_json::Map<String, Value>) -> bool {
+        let Some(max) = self.options.inline_small_objects else {
+            return false;
+        };
+        let entries = self.entries(obj);
+        !entries.is_empty()
+            && entries.len() < max
+            && entries.iter().all(|(_, value)| Self::is_primitive(value))
+    }
+
+    /// Write an object as `{key: value, ...}` on the current line
+    fn write_inline_object(&mut self, obj: &serde_json::Map<String, Value>) -> fmt::Result {
+        self.push('{')?;
+        for (i, (key, value)) in self.entries(obj).into_iter().enumerate() {
+            if i > 0 {
+                self.push(',')?;
+                self.write_colon_space()?;
+            }
+            let encoded_key = self.encode_key(key);
+            self.push_str(&encoded_key)?;
+            self.push(':')?;
+            self.write_colon_space()?;
+            if self.options.redact_keys.contains(key.as_str()) {
+                self.push_str(REDACTED)?;
+            } else {
+                self.encode_primitive(value, Delimiter::Comma)?;
+            }
+        }
+        self.push('}')
+    }
+
+    /// Separate a `:` from the value on the same line, per `space_after_colon`
+    fn write_colon_space(&mut self) -> fmt::Result {
+        if self.options.space_after_colon {
+            self.push(' ')?;
+        }
+        Ok(())
+    }
+
+    /// Check if a string survives the line-oriented `|` block form unchanged
+    ///
+    /// Lines opening with `#` would be dropped as comments under `allow_comments`.
+    fn is_block_safe(s: &str) -> bool {
+        s.contains('\n')
+            && s.split('\n').all(|line| {
+                !line.is_empty()
+                    && line.trim() == line
+                    && !line.starts_with('#')
+                    && !line.chars().any(|c| c.is_control())
+            })
+    }
+
+    /// Write delimiter-separated primitive values on the current line
+    fn write_inline_values(&mut self, arr: &[Value], delim: Delimiter) -> fmt::Result {
+        for (i, val) in arr.iter().enumerate() {
+            if i > 0 {
+                self.push(delim.as_char())?;
             }
+            let s = self.quote_value(val, delim);
+            self.push_str(&s)?;
         }
+        Ok(())
     }
 
     /// Encode array when key has already been written (e.g., "key:")
-    fn encode_array_after_key(&mut self, arr: &[Value], depth: usize) {
+    fn encode_array_after_key(&mut self, arr: &[Value], depth: usize) -> fmt::Result {
         let len = arr.len();
-        let delim = self.options.delimiter;
 
         // Check if array qualifies for tabular format
-        if let Some(fields) = self.detect_tabular(arr) {
+        let tabular = self
+            .detect_tabular(arr)
+            .filter(|_| len >= self.options.tabular_threshold);
+        if let Some(fields) = tabular {
             // Tabular format: key[N]{f1,f2,...}:
-            self.write_array_header(len, delim, Some(&fields));
-
-            for obj in arr.iter() {
-                self.output.push('\n');
-                self.indent(depth + 1);
-
-                if let Value::Object(map) = obj {
-                    // Write values in field order
-                    for (j, field) in fields.iter().enumerate() {
-                        if j > 0 {
-                            self.output.push(delim.as_char());
-                        }
-                        if let Some(val) = map.get(field) {
-                            self.output.push_str(&self.quote_primitive(val, delim));
+            let cells = arr
+                .iter()
+                .filter_map(Value::as_object)
+                .flat_map(|m| m.values());
+            let delim = self.array_delimiter(cells);
+            self.write_array_header(len, delim, Some(&fields))?;
+
+            if self.options.align_columns {
+                self.write_aligned_rows(arr, &fields, delim, depth + 1)?;
+            } else {
+                for obj in arr.iter() {
+                    self.push('\n')?;
+                    self.indent(depth + 1)?;
+
+                    if let Value::Object(map) = obj {
+                        // Write values in field order
+                        for (j, field) in fields.iter().enumerate() {
+                            if j > 0 {
+                                self.push(delim.as_char())?;
+                            }
+                            // Only `tabular_union_keys` leaves fields missing
+                            let cell = match map.get(field) {
+                                Some(val) => self.quote_cell(field, val, delim),
+                                None => "null".to_string(),
+                            };
+                            self.push_str(&cell)?;
                         }
                     }
                 }
             }
-        } else if self.is_inline_primitive_array(arr) {
+        } else if self.is_inline_primitive_array(arr)
+            && self.fits_inline_len(arr)
+            && self.fits_line_width(arr, self.array_delimiter(arr.iter()), self.column)
+        {
             // Inline primitive array: key[N]: v1,v2,...
-            self.write_array_header(len, delim, None);
+            let delim = self.array_delimiter(arr.iter());
+            self.write_array_header(len, delim, None)?;
 
             if !arr.is_empty() {
-                self.output.push(' ');
-                for (i, val) in arr.iter().enumerate() {
-                    if i > 0 {
-                        self.output.push(delim.as_char());
-                    }
-                    self.output.push_str(&self.quote_primitive(val, delim));
-                }
+                self.write_colon_space()?;
+                self.write_inline_values(arr, delim)?;
             }
         } else {
             // Expanded list format: key[N]:
-            self.write_array_header(len, delim, None);
+            let delim = self.options.delimiter;
+            self.write_array_header(len, delim, None)?;
 
+            let same_line = self.options.array_on_same_line && len == 1;
             for item in arr {
-                self.output.push('\n');
-                self.indent(depth + 1);
-                self.output.push_str("- ");
+                if same_line {
+                    self.write_colon_space()?;
+                } else {
+                    self.push('\n')?;
+                    self.indent(depth + 1)?;
+                }
+                if matches!(item, Value::Object(obj) if obj.is_empty()) {
+                    // A bare hyphen, like a bare `key:`, is an empty object
+                    self.push('-')?;
+                    continue;
+                }
+                self.push_str("- ")?;
 
                 match item {
+                    Value::Array(inner) if !inner.iter().all(Self::is_primitive) => {
+                        // Array of arrays or objects: its own header, with its
+                        // rows or items nested under the hyphen
+                        self.encode_array_after_key(inner, depth + 1)?;
+                    }
                     Value::Array(inner) => {
                         // Nested inline array
-                        self.write_array_header(inner.len(), delim, None);
+                        let inner_delim = self.array_delimiter(inner.iter());
+                        self.write_array_header(inner.len(), inner_delim, None)?;
                         if !inner.is_empty() {
-                            self.output.push(' ');
-                            for (i, val) in inner.iter().enumerate() {
-                                if i > 0 {
-                                    self.output.push(delim.as_char());
-                                }
-                                self.output.push_str(&self.quote_primitive(val, delim));
-                            }
+                            self.write_colon_space()?;
+                            self.write_inline_values(inner, inner_delim)?;
                         }
                     }
                     Value::Object(obj) => {
                         // Object as list item
-                        self.encode_object_as_list_item(obj, depth + 1);
+                        self.encode_object_as_list_item(obj, depth + 1)?;
                     }
                     _ => {
                         // Primitive list item
-                        self.encode_primitive(item, delim);
+                        self.encode_primitive(item, delim)?;
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Write tabular rows with cells padded to a common width per column
+    fn write_aligned_rows(
+        &mut self,
+        arr: &[Value],
+        fields: &[String],
+        delim: Delimiter,
+        row_depth: usize,
+    ) -> fmt::Result {
+        // Render all cells first so column widths are known up front
+        let rows: Vec<Vec<String>> = arr
+            .iter()
+            .filter_map(|v| v.as_object())
+            .map(|map| {
+                fields
+                    .iter()
+                    .map(|field| {
+                        map.get(field).map_or_else(
+                            || "null".to_string(),
+                            |val| self.quote_cell(field, val, delim),
+                        )
+                    })
+                    .collect()
+            })
+            .collect();
+
+        let mut widths = vec![0; fields.len()];
+        for row in &rows {
+            for (width, cell) in widths.iter_mut().zip(row) {
+                *width = (*width).max(cell.chars().count());
+            }
+        }
+
+        for row in &rows {
+            self.push('\n')?;
+            self.indent(row_depth)?;
+            for (j, cell) in row.iter().enumerate() {
+                if j > 0 {
+                    self.push(delim.as_char())?;
+                }
+                self.push_str(cell)?;
+                // No padding after the last cell, to avoid trailing whitespace
+                if j + 1 < row.len() {
+                    for _ in cell.chars().count()..widths[j] {
+                        self.push(' ')?;
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Check whether an array has few enough elements for `max_inline_array_len`
+    fn fits_inline_len(&self, arr: &[Value]) -> bool {
+        self.options
+            .max_inline_array_len
+            .is_none_or(|max| arr.len() <= max)
+    }
+
+    /// Check whether an inline array line starting at `column` fits `line_width`
+    fn fits_line_width(&self, arr: &[Value], delim: Delimiter, column: usize) -> bool {
+        let Some(max) = self.options.line_width else {
+            return true;
+        };
+        if arr.is_empty() {
+            return true;
+        }
+
+        // `[N<delim>]: ` followed by the values and the delimiters between them
+        let len_width = if self.options.emit_lengths {
+            arr.len().to_string().len()
+        } else {
+            0
+        };
+        let header = 2
+            + len_width
+            + delim.header_symbol().chars().count()
+            + 1
+            + usize::from(self.options.space_after_colon);
+        let values: usize = arr
+            .iter()
+            .map(|v| self.quote_primitive(v, delim).chars().count())
+            .sum();
+        column + header + values + arr.len() - 1 <= max
+    }
+
+    /// Delimiter for an array holding `values`, honoring `delimiter_auto`
+    fn array_delimiter<'v>(&self, values: impl Iterator<Item = &'v Value>) -> Delimiter {
+        let configured = self.options.delimiter;
+        if !self.options.delimiter_auto {
+            return configured;
+        }
+
+        let strings: Vec<&str> = values.filter_map(Value::as_str).collect();
+        // `min_by_key` keeps the first minimum, so ties favor the configured delimiter
+        [
+            configured,
+            Delimiter::Comma,
+            Delimiter::Pipe,
+            Delimiter::Tab,
+        ]
+        .into_iter()
+        .min_by_key(|d| strings.iter().filter(|s| s.contains(d.as_char())).count())
+        .unwrap_or(configured)
+    }
+
+    /// Collect `describe` lines for `value` and its descendants
+    ///
+    /// `list_item` marks values that are items of a list array, where arrays
+    /// of primitives are always written inline.
+    fn describe_into(&self, value: &Value, path: String, list_item: bool, lines: &mut Vec<String>) {
+        match value {
+            Value::Object(obj) => {
+                for (key, child) in self.entries(obj) {
+                    let child_path = format!("{}.{}", path, self.encode_key(key));
+                    self.describe_into(child, child_path, false, lines);
+                }
+            }
+            Value::Array(arr) => {
+                // Primitive arrays inside list items are always written inline
+                let forced_inline = list_item && arr.iter().all(Self::is_primitive);
+                let tabular = if forced_inline {
+                    None
+                } else {
+                    self.detect_tabular(arr)
+                };
+
+                let description = if forced_inline {
+                    "inline: array as list item".to_string()
+                } else if let Some(fields) = &tabular {
+                    if arr.len() < self.options.tabular_threshold {
+                        "list: fewer rows than tabular_threshold".to_string()
+                    } else {
+                        let keys: Vec<String> = fields.iter().map(|f| self.encode_key(f)).collect();
+                        format!("tabular: uniform object keys [{}]", keys.join(","))
                     }
+                } else if arr.is_empty() {
+                    "inline: empty array".to_string()
+                } else if self.is_inline_primitive_array(arr) {
+                    // Indentation and key are not tracked here, so only the
+                    // header and values count towards `line_width`
+                    if !self.fits_inline_len(arr) {
+                        "list: more elements than max_inline_array_len".to_string()
+                    } else if self.fits_line_width(arr, self.array_delimiter(arr.iter()), 0) {
+                        "inline: primitives of a single type".to_string()
+                    } else {
+                        "list: inline form exceeds line_width".to_string()
+                    }
+                } else if arr.iter().all(Value::is_object) {
+                    "list: objects with differing keys or nested values".to_string()
+                } else if arr.iter().all(|v| !v.is_object() && !v.is_array()) {
+                    "list: mixed primitive types".to_string()
+                } else {
+                    "list: mixed types".to_string()
+                };
+                let is_list = description.starts_with("list");
+                lines.push(format!("{}: {}", path, description));
+
+                for (i, item) in arr.iter().enumerate() {
+                    self.describe_into(item, format!("{}[{}]", path, i), is_list, lines);
                 }
             }
+            _ => {}
         }
     }
 
     /// Encode an array at root level (no key prefix)
     /// This delegates to encode_array_after_key since the logic is identical
     /// for both root-level and field-level arrays
-    fn encode_array(&mut self, arr: &[Value], depth: usize, _key: Option<&str>) {
-        self.encode_array_after_key(arr, depth);
+    fn encode_array(&mut self, arr: &[Value], depth: usize, _key: Option<&str>) -> fmt::Result {
+        self.encode_array_after_key(arr, depth)
     }
 
     /// Encode object as a list item (first field on hyphen line)
-    fn encode_object_as_list_item(&mut self, obj: &serde_json::Map<String, Value>, depth: usize) {
-        let mut first = true;
-
-        for (key, value) in obj.iter() {
-            if !first {
-                self.output.push('\n');
-                self.indent(depth);
-            }
-
-            if first {
-                // First field on hyphen line
-                first = false;
+    fn encode_object_as_list_item(
+        &mut self,
+        obj: &serde_json::Map<String, Value>,
+        depth: usize,
+    ) -> fmt::Result {
+        for (i, (key, value)) in self.entries(obj).into_iter().enumerate() {
+            // The first field shares the hyphen line, so its key sits one level
+            // deeper than the keys of the remaining fields
+            let on_hyphen_line = i == 0;
+            if !on_hyphen_line {
+                self.push('\n')?;
+                self.indent(depth)?;
             }
 
-            self.output.push_str(&self.encode_key(key));
-            self.output.push(':');
+            self.base64_field = self.options.base64_keys.contains(key.as_str());
+            let encoded_key = self.encode_key(key);
+            self.push_str(&encoded_key)?;
 
+            if self.options.redact_keys.contains(key.as_str()) {
+                self.push(':')?;
+                self.write_colon_space()?;
+                self.push_str(REDACTED)?;
+                continue;
+            }
+            if let Value::Array(arr) = value {
+                // The array header follows the key directly, as in `key[N]:`
+                self.encode_array_after_key(arr, depth)?;
+                continue;
+            }
+            self.push(':')?;
             match value {
                 Value::Object(nested) if nested.is_empty() => {
                     continue;
                 }
-                Value::Object(nested) => {
-                    self.output.push('\n');
-                    self.encode_object(nested, if first { depth + 2 } else { depth + 1 });
+                Value::Object(nested) if self.is_inline_object(nested) => {
+                    self.write_colon_space()?;
+                    self.write_inline_object(nested)?;
                 }
-                Value::Array(arr) => {
-                    // Array as object field in list item
-                    self.encode_array_after_key(arr, depth);
+                Value::Object(nested) => {
+                    // encode_object writes its own leading newline
+                    let child_depth = if on_hyphen_line { depth + 2 } else { depth + 1 };
+                    self.encode_object(nested, child_depth)?;
                 }
                 _ => {
-                    self.output.push(' ');
-                    self.encode_primitive(value, self.options.delimiter);
+                    let block_depth = if on_hyphen_line { depth + 2 } else { depth + 1 };
+                    self.write_field_primitive(value, block_depth)?;
                 }
             }
         }
+        Ok(())
+    }
+
+    /// Object entries in output order (sorted when `sort_keys` is set, led by
+    /// `key_order`, without `null` values when `skip_nulls` is set)
+    fn entries<'v>(&self, obj: &'v serde_json::Map<String, Value>) -> Vec<(&'v String, &'v Value)> {
+        let mut entries: Vec<_> = obj
+            .iter()
+            .filter(|(_, value)| !(self.options.skip_nulls && value.is_null()))
+            .collect();
+        if self.options.sort_keys {
+            entries.sort_by(|a, b| a.0.cmp(b.0));
+        }
+        if let Some(order) = &self.options.key_order {
+            // Stable, so unlisted keys keep their relative order
+            entries.sort_by_key(|(key, _)| {
+                order.iter().position(|k| k == *key).unwrap_or(order.len())
+            });
+        }
+        entries
+    }
+
+    /// Check if a value is a primitive (not an object or array)
+    fn is_primitive(value: &Value) -> bool {
+        !value.is_object() && !value.is_array()
     }
 
     /// Check if array should use inline format (all same primitive type)
@@ -267,17 +1207,31 @@ impl<'a> Encoder<'a> {
             return None;
         }
 
+        if self.options.tabular_union_keys {
+            return self.detect_union_tabular(&objects);
+        }
+
         // Get field names from first object
-        let first = objects[0];
-        let fields: Vec<String> = first.keys().cloned().collect();
+        let fields: Vec<String> = self
+            .entries(objects[0])
+            .into_iter()
+            .map(|(key, _)| key.clone())
+            .collect();
+        // Rows of empty objects would be blank lines
+        if fields.is_empty() {
+            return None;
+        }
 
         // All objects must have same keys and all values must be primitives
         for obj in &objects {
-            if obj.len() != fields.len() {
+            if self.entries(obj).len() != fields.len() {
                 return None;
             }
             for field in &fields {
                 let value = obj.get(field)?;
+                if self.options.skip_nulls && value.is_null() {
+                    return None;
+                }
                 // Must be primitive (not object or array)
                 if !matches!(
                     value,
@@ -291,28 +1245,80 @@ impl<'a> Encoder<'a> {
         Some(fields)
     }
 
-    /// Write array header: `[N<delim>]` or `[N<delim>]{fields}:`
-    fn write_array_header(&mut self, len: usize, delim: Delimiter, fields: Option<&[String]>) {
-        self.output.push('[');
-        self.output.push_str(&len.to_string());
-        self.output.push_str(delim.header_symbol());
-        self.output.push(']');
+    /// Tabular fields for `tabular_union_keys`: every key of any object, when
+    /// all values are primitives and no object is empty
+    fn detect_union_tabular(
+        &self,
+        objects: &[&serde_json::Map<String, Value>],
+    ) -> Option<Vec<String>> {
+        let mut union = serde_json::Map::new();
+        for obj in objects {
+            let entries = self.entries(obj);
+            if entries.is_empty() {
+                return None;
+            }
+            for (key, value) in entries {
+                if !Self::is_primitive(value) {
+                    return None;
+                }
+                if !union.contains_key(key) {
+                    union.insert(key.clone(), value.clone());
+                }
+            }
+        }
+
+        // Order the columns as the keys of a single object would be
+        Some(
+            self.entries(&union)
+                .into_iter()
+                .map(|(key, _)| key.clone())
+                .collect(),
+        )
+    }
+
+    /// Write array header: `[N<delim>]` or `[N<delim>]{fields}:` (N omitted unless `emit_lengths`)
+    fn write_array_header(
+        &mut self,
+        len: usize,
+        delim: Delimiter,
+        fields: Option<&[String]>,
+    ) -> fmt::Result {
+        match self.options.spec_version {
+            SpecVersion::V2_0 => {
+                self.push('[')?;
+                if self.options.emit_lengths {
+                    self.push_str(&len.to_string())?;
+                }
+                self.push_str(delim.header_symbol())?;
+                self.push(']')?;
+            }
+        }
         if let Some(fields) = fields {
-            self.output.push('{');
+            self.push('{')?;
             for (i, field) in fields.iter().enumerate() {
                 if i > 0 {
-                    self.output.push(delim.as_char());
+                    self.push(delim.as_char())?;
                 }
-                self.output.push_str(&self.encode_key(field));
+                let encoded_key = self.encode_key(field);
+                self.push_str(&encoded_key)?;
             }
-            self.output.push('}');
+            self.push('}')?;
         }
 
-        self.output.push(':');
+        self.push(':')
     }
 
     /// Encode a key (with quoting if needed)
     fn encode_key(&self, key: &str) -> String {
+        let cased;
+        let key = match self.options.key_case {
+            Some(case) => {
+                cased = case.apply(key);
+                cased.as_str()
+            }
+            None => key,
+        };
+
         // Keys must be quoted unless they match: ^[A-Za-z_][A-Za-z0-9_.]*$
         let needs_quoting = key.is_empty()
             || (!key.chars().next().unwrap().is_ascii_alphabetic() && !key.starts_with('_'))
@@ -328,8 +1334,9 @@ impl<'a> Encoder<'a> {
     }
 
     /// Encode primitive with delimiter-aware quoting
-    fn encode_primitive(&mut self, value: &Value, delim: Delimiter) {
-        self.output.push_str(&self.quote_primitive(value, delim));
+    fn encode_primitive(&mut self, value: &Value, delim: Delimiter) -> fmt::Result {
+        let s = self.quote_primitive(value, delim);
+        self.push_str(&s)
     }
 
     /// Quote primitive value with delimiter awareness
@@ -343,16 +1350,62 @@ impl<'a> Encoder<'a> {
         }
     }
 
+    /// Quote a tabular cell, writing base64 values of `base64_keys` fields verbatim
+    fn quote_cell(&self, field: &str, value: &Value, delim: Delimiter) -> String {
+        if self.options.redact_keys.contains(field) {
+            return REDACTED.to_string();
+        }
+        match value {
+            Value::String(s)
+                if self.options.base64_keys.contains(field) && self.is_verbatim_base64(s) =>
+            {
+                s.clone()
+            }
+            _ => self.quote_value(value, delim),
+        }
+    }
+
+    /// Quote a primitive in value position, where `unquote_iso_dates` applies
+    fn quote_value(&self, value: &Value, delim: Delimiter) -> String {
+        match value {
+            Value::String(s)
+                if self.options.unquote_iso_dates
+                    && !self.options.quote_all_strings
+                    && is_iso_timestamp(s) =>
+            {
+                s.clone()
+            }
+            _ => self.quote_primitive(value, delim),
+        }
+    }
+
+    /// Check if a `base64_keys` value is valid base64 that decodes back as a string
+    fn is_verbatim_base64(&self, s: &str) -> bool {
+        is_base64(s) && !matches!(s, "true" | "null") && !self.is_numeric_like(s)
+    }
+
     /// Quote string with delimiter-aware rules (§7.2)
     fn quote_string(&self, s: &str, delim: Delimiter) -> String {
-        let needs_quoting = s.is_empty()
-            || s.starts_with(' ')
-            || s.ends_with(' ')
+        if self.needs_quoting(s, delim) {
+            self.quote_and_escape(s)
+        } else {
+            s.to_string()
+        }
+    }
+
+    /// Check if a string value must be quoted to survive decoding (§7.2)
+    fn needs_quoting(&self, s: &str, delim: Delimiter) -> bool {
+        self.options.quote_all_strings
+            || s.is_empty()
+            || s.starts_with(char::is_whitespace)
+            || s.ends_with(char::is_whitespace)
             || s == "true"
             || s == "false"
             || s == "null"
             || s == "-"
             || s.starts_with('-')
+            // A line opening with `#` reads as a comment under `allow_comments`
+            || s.starts_with('#')
             || s.contains(':')
             || s.contains('"')
             || s.contains('\\')
@@ -360,36 +1413,18 @@ impl<'a> Encoder<'a> {
             || s.contains(']')
             || s.contains('{')
             || s.contains('}')
-            || s.contains('\n')
-            || s.contains('\r')
-            || s.contains('\t')
+            || s.chars().any(char::is_control)
+            || (self.options.escape_unicode && !s.is_ascii())
             || s.contains(delim.as_char())
-            || self.is_numeric_like(s);
-
-        if needs_quoting {
-            self.quote_and_escape(s)
-        } else {
-            s.to_string()
-        }
+            || self.is_numeric_like(s)
     }
 
     /// Check if string looks like a number
-    /// Returns true if the string could be parsed as a numeric value,
-    /// including edge cases like leading zeros (e.g., "007", "0123")
+    /// Returns true if the string matches the TOON number grammar, including
+    /// forms with leading zeros (e.g., "007", "0123") which are quoted to
+    /// avoid any ambiguity. Words like "inf" or "NaN" are not numeric.
     fn is_numeric_like(&self, s: &str) -> bool {
-        // Check for leading zeros which need quoting per TOON spec
-        if s.starts_with('0') && s.len() > 1 {
-            if let Some(second_char) = s.chars().nth(1) {
-                // "0." and "-0" are valid, but "05", "0123" etc. need quoting
-                if second_char.is_ascii_digit() {
-                    return true;
-                }
-            }
-        }
-
-        // Use standard library parsing to detect numeric patterns
-        // This handles integers, floats, scientific notation, etc.
-        s.parse::<f64>().is_ok()
+        is_numeric_literal(s)
     }
 
     /// Quote and escape a string (§7.1)
@@ -404,6 +1439,14 @@ impl<'a> Encoder<'a> {
                 '\n' => result.push_str("\\n"),
                 '\r' => result.push_str("\\r"),
                 '\t' => result.push_str("\\t"),
+                // Remaining control characters would otherwise be written raw
+                c if c.is_control() => result.push_str(&format!("\\u{:04x}", c as u32)),
+                c if self.options.escape_unicode && !c.is_ascii() => {
+                    let mut units = [0u16; 2];
+                    for unit in c.encode_utf16(&mut units) {
+                        result.push_str(&format!("\\u{:04x}", unit));
+                    }
+                }
                 _ => result.push(c),
             }
         }
@@ -419,6 +1462,10 @@ impl<'a> Encoder<'a> {
             i.to_string()
         } else if let Some(u) = n.as_u64() {
             u.to_string()
+        } else if is_integer_literal(&n.to_string()) {
+            // Integer beyond the 64-bit range (serde_json's arbitrary_precision):
+            // emit the exact digits instead of round-tripping through f64
+            n.to_string()
         } else if let Some(f) = n.as_f64() {
             // Handle special cases - convert to null per TOON spec
             if f.is_nan() || f.is_infinite() {
@@ -431,14 +1478,21 @@ impl<'a> Encoder<'a> {
                 return "0".to_string();
             }
 
-            // Format the number, then convert scientific notation if present
+            if self.options.js_compatible_numbers {
+                return Self::js_number_string(f);
+            }
+
+            // The default formatting yields the shortest round-trip digits,
+            // possibly in scientific notation
             let mut s = n.to_string();
 
             // Convert scientific notation (e.g., "1.5e10") to decimal form
             if s.contains('e') || s.contains('E') {
-                // Parse and reformat without scientific notation
-                // For very large/small numbers, this may produce long strings
-                s = format!("{:.}", f);
+                if self.options.preserve_scientific {
+                    // Shortest digits have no trailing zeros to strip
+                    return s;
+                }
+                s = Self::expand_exponent(&s);
             }
 
             // Remove trailing zeros after decimal point
@@ -457,11 +1511,139 @@ impl<'a> Encoder<'a> {
         }
     }
 
+    /// Format a finite, non-zero float as ECMAScript's Number::toString does
+    fn js_number_string(f: f64) -> String {
+        // `{:e}` yields the shortest round-trip digits as `d.ddde<exp>`
+        let sci = format!("{:e}", f.abs());
+        let (mantissa, exp) = sci.split_once('e').unwrap_or((&sci, "0"));
+        let digits = mantissa.replace('.', "");
+        let k = digits.len() as i64;
+        // The value is 0.<digits> * 10^n
+        let n = exp.parse::<i64>().unwrap_or(0) + 1;
+
+        let mut out = String::new();
+        if f < 0.0 {
+            out.push('-');
+        }
+        if k <= n && n <= 21 {
+            out.push_str(&digits);
+            out.extend(core::iter::repeat_n('0', (n - k) as usize));
+        } else if 0 < n && n <= 21 {
+            out.push_str(&digits[..n as usize]);
+            out.push('.');
+            out.push_str(&digits[n as usize..]);
+        } else if -6 < n && n <= 0 {
+            out.push_str("0.");
+            out.extend(core::iter::repeat_n('0', n.unsigned_abs() as usize));
+            out.push_str(&digits);
+        } else {
+            out.push_str(&digits[..1]);
+            if k > 1 {
+                out.push('.');
+                out.push_str(&digits[1..]);
+            }
+            out.push('e');
+            out.push(if n > 0 { '+' } else { '-' });
+            out.push_str(&(n - 1).unsigned_abs().to_string());
+        }
+        out
+    }
+
+    /// Rewrite scientific notation (e.g. `1.5e-7`) as plain decimal (`0.00000015`)
+    ///
+    /// The decimal point is shifted textually over the digits already chosen,
+    /// so the shortest round-trip digits are kept and the output length is
+    /// bounded by the digit count plus the exponent.
+    fn expand_exponent(s: &str) -> String {
+        let (mantissa, exp) = s.split_once(['e', 'E']).unwrap_or((s, "0"));
+        let exp: i64 = exp.parse().unwrap_or(0);
+        let (sign, mantissa) = match mantissa.strip_prefix('-') {
+            Some(m) => ("-", m),
+            None => ("", mantissa),
+        };
+        let (int_part, frac_part) = mantissa.split_once('.').unwrap_or((mantissa, ""));
+
+        let all_digits = format!("{}{}", int_part, frac_part);
+        let digits = all_digits.trim_start_matches('0');
+        if digits.is_empty() {
+            return "0".to_string();
+        }
+        // Position of the decimal point within `digits`
+        let point = int_part.len() as i64 + exp - (all_digits.len() - digits.len()) as i64;
+
+        let mut out = String::with_capacity(digits.len() + point.unsigned_abs() as usize + 3);
+        out.push_str(sign);
+        if point <= 0 {
+            out.push_str("0.");
+            out.extend(core::iter::repeat_n('0', point.unsigned_abs() as usize));
+            out.push_str(digits);
+        } else if point as usize >= digits.len() {
+            out.push_str(digits);
+            out.extend(core::iter::repeat_n('0', point as usize - digits.len()));
+        } else {
+            let (int_digits, frac_digits) = digits.split_at(point as usize);
+            out.push_str(int_digits);
+            out.push('.');
+            out.push_str(frac_digits);
+        }
+        out
+    }
+
     /// Write indentation
-    fn indent(&mut self, depth: usize) {
-        for _ in 0..(depth * self.options.indent) {
-            self.output.push(' ');
+    fn indent(&mut self, depth: usize) -> fmt::Result {
+        let (unit, width) = match self.options.indent {
+            IndentStyle::Spaces(n) => (' ', n),
+            IndentStyle::Tabs => ('\t', 1),
+        };
+        debug_assert!(
+            width > 0 || depth == 0,
+            "nested output needs a non-zero indent; see EncoderOptions::validate"
+        );
+        for _ in 0..(depth * width) {
+            self.push(unit)?;
+        }
+        Ok(())
+    }
+}
+
+/// Check if a string is non-empty standard base64 with `=` padding
+fn is_base64(s: &str) -> bool {
+    let data = s.trim_end_matches('=');
+    !s.is_empty()
+        && s.len().is_multiple_of(4)
+        && s.len() - data.len() <= 2
+        && data
+            .bytes()
+            .all(|b| b.is_ascii_alphanumeric() || b == b'+' || b == b'/')
+}
+
+/// Check if a string is an ISO-8601 date-time, `YYYY-MM-DDTHH:MM:SS` with an
+/// optional `.fraction` and a `Z` or `+HH:MM` offset
+fn is_iso_timestamp(s: &str) -> bool {
+    fn matches_digits(s: &[u8], template: &[u8]) -> bool {
+        s.len() == template.len()
+            && s.iter().zip(template).all(|(&b, &t)| match t {
+                b'0' => b.is_ascii_digit(),
+                _ => b == t,
+            })
+    }
+
+    let bytes = s.as_bytes();
+    if bytes.len() < 19 || !matches_digits(&bytes[..19], b"0000-00-00T00:00:00") {
+        return false;
+    }
+    let mut rest = &bytes[19..];
+    if let Some(fraction) = rest.strip_prefix(b".") {
+        let digits = fraction.iter().take_while(|b| b.is_ascii_digit()).count();
+        if digits == 0 {
+            return false;
         }
+        rest = &fraction[digits..];
+    }
+    match rest {
+        [] | [b'Z'] => true,
+        [b'+' | b'-', offset @ ..] => matches_digits(offset, b"00:00"),
+        _ => false,
     }
 }
 
@@ -581,6 +1763,7 @@ mod tests {
     }
 
     #[test]
+    #[allow(clippy::approx_constant)]
     fn test_number_normalization() {
         let data = json!({
             "int": 42,
@@ -600,8 +1783,9 @@ mod tests {
             ]
         });
         let options = EncoderOptions {
-            indent: 2,
+            indent: IndentStyle::Spaces(2),
             delimiter: Delimiter::Tab,
+            ..Default::default()
         };
         let result = encode(&data, &options);
         assert_eq!(result, "items[2\t]{id\tname}:\n  1\tA\n  2\tB");
@@ -613,13 +1797,28 @@ mod tests {
             "tags": ["a", "b", "c"]
         });
         let options = EncoderOptions {
-            indent: 2,
+            indent: IndentStyle::Spaces(2),
             delimiter: Delimiter::Pipe,
+            ..Default::default()
         };
         let result = encode(&data, &options);
         assert_eq!(result, "tags[3|]: a|b|c");
     }
 
+    #[test]
+    fn test_semicolon_delimiter() {
+        let data = json!({
+            "tags": ["a", "b", "c"]
+        });
+        let options = EncoderOptions {
+            indent: IndentStyle::Spaces(2),
+            delimiter: Delimiter::Semicolon,
+            ..Default::default()
+        };
+        let result = encode(&data, &options);
+        assert_eq!(result, "tags[3;]: a;b;c");
+    }
+
     #[test]
     fn test_deep_nesting() {
         let data = json!({
@@ -659,4 +1858,1143 @@ mod tests {
         let result = encode(&data, &EncoderOptions::default());
         assert_eq!(result, "matrix[2]:\n  - [2]: 1,2\n  - [2]: 3,4");
     }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn test_encode_to_writer_matches_encode() {
+        let data = json!({
+            "name": "Test",
+            "items": [
+                {"id": 1, "value": "A"},
+                {"id": 2, "value": "B"}
+            ],
+            "matrix": [[1, 2], [3, 4]]
+        });
+        let mut buf = Vec::new();
+        encode_to_writer(&data, &EncoderOptions::default(), &mut buf).unwrap();
+        assert_eq!(
+            String::from_utf8(buf).unwrap(),
+            encode(&data, &EncoderOptions::default())
+        );
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn test_encode_to_writer_propagates_errors() {
+        struct FailingWriter;
+
+        impl io::Write for FailingWriter {
+            fn write(&mut self, _buf: &[u8]) -> io::Result<usize> {
+                Err(io::Error::other("sink closed"))
+            }
+
+            fn flush(&mut self) -> io::Result<()> {
+                Ok(())
+            }
+        }
+
+        let data = json!({"name": "Alice"});
+        let result = encode_to_writer(&data, &EncoderOptions::default(), &mut FailingWriter);
+        assert_eq!(result.unwrap_err().to_string(), "sink closed");
+    }
+
+    #[test]
+    fn test_sort_keys() {
+        let data = json!({"b": 1, "a": 2});
+        let options = EncoderOptions {
+            sort_keys: true,
+            ..Default::default()
+        };
+        assert_eq!(encode(&data, &options), "a: 2\nb: 1");
+        assert_eq!(encode(&data, &EncoderOptions::default()), "b: 1\na: 2");
+    }
+
+    #[test]
+    fn test_sort_keys_tabular_and_nested() {
+        let data = json!({
+            "z": {"y": 1, "x": 2},
+            "rows": [
+                {"name": "Alice", "id": 1},
+                {"name": "Bob", "id": 2}
+            ]
+        });
+        let options = EncoderOptions {
+            sort_keys: true,
+            ..Default::default()
+        };
+        assert_eq!(
+            encode(&data, &options),
+            "rows[2]{id,name}:\n  1,Alice\n  2,Bob\nz:\n  x: 2\n  y: 1"
+        );
+    }
+
+    #[test]
+    fn test_list_item_nested_object_first_field() {
+        let data = json!({
+            "items": [{"a": {"b": 1}, "c": {"d": 2}}]
+        });
+        let result = encode(&data, &EncoderOptions::default());
+        assert_eq!(result, "items[1]:\n  - a:\n      b: 1\n  c:\n    d: 2");
+    }
+
+    #[test]
+    fn test_toon_display() {
+        let data = json!({
+            "user": {"name": "Bob", "id": 123},
+            "tags": ["a", "b"]
+        });
+        let options = EncoderOptions::default();
+        let expected = encode(&data, &options);
+        assert_eq!(format!("{}", ToonDisplay::new(&data, &options)), expected);
+        assert_eq!(data.to_toon(&options).to_string(), expected);
+    }
+
+    #[test]
+    fn test_trailing_newline() {
+        let options = EncoderOptions {
+            trailing_newline: true,
+            ..Default::default()
+        };
+        assert_eq!(
+            encode(&json!({"a": 1, "b": [1, 2]}), &options),
+            "a: 1\nb[2]: 1,2\n"
+        );
+        assert_eq!(encode(&json!(42), &options), "42\n");
+        assert_eq!(encode(&json!({}), &options), "");
+        assert_eq!(encode(&json!({"a": 1}), &EncoderOptions::default()), "a: 1");
+    }
+
+    #[test]
+    fn test_quote_all_strings() {
+        let options = EncoderOptions {
+            quote_all_strings: true,
+            ..Default::default()
+        };
+        assert_eq!(
+            encode(&json!({"name": "Alice"}), &options),
+            "name: \"Alice\""
+        );
+
+        let data = json!({
+            "rows": [{"id": 1, "tag": "x"}, {"id": 2, "tag": "y"}],
+            "list": ["a", "b"]
+        });
+        assert_eq!(
+            encode(&data, &options),
+            "rows[2]{id,tag}:\n  1,\"x\"\n  2,\"y\"\nlist[2]: \"a\",\"b\""
+        );
+    }
+
+    #[test]
+    fn test_multiline_strings() {
+        let options = EncoderOptions {
+            multiline_strings: true,
+            ..Default::default()
+        };
+        let data = json!({
+            "query": "SELECT *\nFROM users\nWHERE id = 1",
+            "padded": "a\n  b",
+            "single": "one line"
+        });
+        assert_eq!(
+            encode(&data, &options),
+            "query: |\n  SELECT *\n  FROM users\n  WHERE id = 1\npadded: \"a\\n  b\"\nsingle: one line"
+        );
+    }
+
+    #[test]
+    fn test_tab_indentation() {
+        let data = json!({
+            "user": {"name": "Bob", "tags": ["a", "b"]},
+            "rows": [{"id": 1, "v": ""}, {"id": 2, "v": "x"}]
+        });
+        let options = EncoderOptions {
+            indent: IndentStyle::Tabs,
+            delimiter: Delimiter::Tab,
+            ..Default::default()
+        };
+        assert_eq!(
+            encode(&data, &options),
+            "user:\n\tname: Bob\n\ttags[2\t]: a\tb\nrows[2\t]{id\tv}:\n\t1\t\"\"\n\t2\tx"
+        );
+    }
+
+    #[test]
+    fn test_align_columns() {
+        let data = json!({
+            "users": [
+                {"id": 1, "name": "Alice", "role": "admin"},
+                {"id": 100, "name": "Bob", "role": "user"}
+            ]
+        });
+        let options = EncoderOptions {
+            align_columns: true,
+            ..Default::default()
+        };
+        assert_eq!(
+            encode(&data, &options),
+            "users[2]{id,name,role}:\n  1  ,Alice,admin\n  100,Bob  ,user"
+        );
+    }
+
+    #[test]
+    fn test_numeric_like_quoting() {
+        let data = json!({
+            "values": ["inf", "NaN", "Infinity", "1e3", "1_000", "2.5E-3", ".5", "5.", "007", "+5"]
+        });
+        let result = encode(&data, &EncoderOptions::default());
+        assert_eq!(
+            result,
+            "values[10]: inf,NaN,Infinity,\"1e3\",1_000,\"2.5E-3\",.5,5.,\"007\",+5"
+        );
+    }
+
+    #[test]
+    fn test_delimiter_auto() {
+        let data = json!({
+            "places": [
+                {"city": "Paris, France", "code": "FR"},
+                {"city": "Rome, Italy", "code": "IT"}
+            ],
+            "tags": ["a|b", "c|d", "e,f"],
+            "plain": ["x", "y"],
+            "mixed": [1, ["a,b", "c"]]
+        });
+        let options = EncoderOptions {
+            delimiter_auto: true,
+            ..Default::default()
+        };
+        let result = encode(&data, &options);
+        assert_eq!(
+            result,
+            "places[2|]{city|code}:\n  Paris, France|FR\n  Rome, Italy|IT\ntags[3\t]: a|b\tc|d\te,f\nplain[2]: x,y\nmixed[2]:\n  - 1\n  - [2|]: a,b|c"
+        );
+        assert_eq!(
+            crate::decode(&result, &crate::DecoderOptions::default()).unwrap(),
+            data
+        );
+    }
+
+    #[test]
+    fn test_extreme_floats() {
+        let opts = EncoderOptions::default();
+        let cases = [
+            (1e-300, format!("0.{}1", "0".repeat(299))),
+            (1e300, format!("1{}", "0".repeat(300))),
+            (
+                1.7976931348623157e308,
+                format!("17976931348623157{}", "0".repeat(292)),
+            ),
+            (-1.5e-7, "-0.00000015".to_string()),
+            (6.022e23, "602200000000000000000000".to_string()),
+            (-0.0, "0".to_string()),
+        ];
+        for (f, expected) in cases {
+            let result = encode(&json!({ "x": f }), &opts);
+            assert_eq!(result, format!("x: {}", expected));
+            assert_eq!(expected.parse::<f64>().unwrap(), f);
+        }
+    }
+
+    #[test]
+    fn test_preserve_scientific() {
+        let data = json!({"avogadro": 6.022e23, "tiny": 1.5e-7, "pi": 3.5});
+        let options = EncoderOptions {
+            preserve_scientific: true,
+            ..Default::default()
+        };
+        let result = encode(&data, &options);
+        assert_eq!(result, "avogadro: 6.022e+23\ntiny: 1.5e-7\npi: 3.5");
+        assert_eq!(
+            crate::decode(&result, &crate::DecoderOptions::default()).unwrap(),
+            data
+        );
+    }
+
+    #[test]
+    fn test_describe() {
+        let data = json!({
+            "users": [{"id": 1, "name": "Alice"}, {"id": 2, "name": "Bob"}],
+            "tags": ["a", "b"],
+            "empty": [],
+            "items": [
+                {"id": 1, "parts": [1, true]},
+                {"id": 2},
+                [3, 4]
+            ],
+            "nested": {"rows": [{"x": 1, "y": [1]}]}
+        });
+        assert_eq!(
+            describe(&data, &EncoderOptions::default()),
+            "$.users: tabular: uniform object keys [id,name]\n\
+             $.tags: inline: primitives of a single type\n\
+             $.empty: inline: empty array\n\
+             $.items: list: mixed types\n\
+             $.items[0].parts: list: mixed primitive types\n\
+             $.items[2]: inline: array as list item\n\
+             $.nested.rows: list: objects with differing keys or nested values\n\
+             $.nested.rows[0].y: inline: primitives of a single type"
+        );
+        assert_eq!(describe(&json!({"a": 1}), &EncoderOptions::default()), "");
+    }
+
+    #[test]
+    fn test_emit_lengths_false() {
+        let data = json!({
+            "users": [{"id": 1, "name": "Alice"}, {"id": 2, "name": "Bob"}],
+            "tags": ["a", "b", "c"],
+            "empty": [],
+            "items": [1, {"k": "v"}, ["x", "y"]]
+        });
+        let options = EncoderOptions {
+            emit_lengths: false,
+            delimiter: Delimiter::Pipe,
+            ..Default::default()
+        };
+        let result = encode(&data, &options);
+        assert_eq!(
+            result,
+            "users[|]{id|name}:\n  1|Alice\n  2|Bob\ntags[|]: a|b|c\nempty[|]:\nitems[|]:\n  - 1\n  - k: v\n  - [|]: x|y"
+        );
+        assert_eq!(
+            crate::decode(&result, &crate::DecoderOptions::default()).unwrap(),
+            data
+        );
+    }
+
+    #[test]
+    fn test_root_scalars_round_trip() {
+        let options = EncoderOptions::default();
+        let cases = [
+            (json!("42"), "\"42\""),
+            (json!("hi"), "hi"),
+            (json!("true"), "\"true\""),
+            (json!("null"), "\"null\""),
+            (json!(""), "\"\""),
+            (json!("a: b"), "\"a: b\""),
+            (json!("[3]: x"), "\"[3]: x\""),
+            (json!("- x"), "\"- x\""),
+            (json!(42), "42"),
+            (json!(-1.5), "-1.5"),
+            (json!(true), "true"),
+            (json!(false), "false"),
+            (json!(null), "null"),
+        ];
+        for (value, expected) in cases {
+            let result = encode(&value, &options);
+            assert_eq!(result, expected);
+            assert_eq!(
+                crate::decode(&result, &crate::DecoderOptions::default()).unwrap(),
+                value
+            );
+        }
+    }
+
+    #[test]
+    fn test_line_width() {
+        let data = json!({
+            "short": ["a", "b"],
+            "nested": {"long": ["alpha", "beta", "gamma", "delta"]},
+            "exact": [12345, 67890]
+        });
+        let options = EncoderOptions {
+            line_width: Some(24),
+            ..Default::default()
+        };
+        let result = encode(&data, &options);
+        assert_eq!(
+            result,
+            "short[2]: a,b\nnested:\n  long[4]:\n    - alpha\n    - beta\n    - gamma\n    - delta\nexact[2]: 12345,67890"
+        );
+        assert!(result.lines().all(|line| line.chars().count() <= 24));
+        assert_eq!(
+            crate::decode(&result, &crate::DecoderOptions::default()).unwrap(),
+            data
+        );
+    }
+
+    #[test]
+    fn test_type_mix_round_trip() {
+        // (array, expected layout of `items`)
+        let cases = [
+            (json!([1, 2.5]), "items[2]: 1,2.5"),
+            (json!(["1", "2"]), "items[2]: \"1\",\"2\""),
+            (json!(["a", "1e3", "05"]), "items[3]: a,\"1e3\",\"05\""),
+            (json!([null, null]), "items[2]: null,null"),
+            (json!(["true", "null"]), "items[2]: \"true\",\"null\""),
+            (json!([1, "2"]), "items[2]:\n  - 1\n  - \"2\""),
+            (
+                json!([1, "text", true]),
+                "items[3]:\n  - 1\n  - text\n  - true",
+            ),
+            (
+                json!([false, "false"]),
+                "items[2]:\n  - false\n  - \"false\"",
+            ),
+            (json!([1, null]), "items[2]:\n  - 1\n  - null"),
+            (json!(["null", null]), "items[2]:\n  - \"null\"\n  - null"),
+            (json!([{"a": 1}, 1]), "items[2]:\n  - a: 1\n  - 1"),
+            (json!([{}, 1]), "items[2]:\n  -\n  - 1"),
+            (json!([{}, {"b": 1}]), "items[2]:\n  -\n  - b: 1"),
+            (json!([{}, {}]), "items[2]:\n  -\n  -"),
+            (
+                json!([[1, "2"], ["a"]]),
+                "items[2]:\n  - [2]: 1,\"2\"\n  - [1]: a",
+            ),
+            (
+                json!([[1, {"a": 1}]]),
+                "items[1]:\n  - [2]:\n    - 1\n    - a: 1",
+            ),
+            (json!([[[1]]]), "items[1]:\n  - [1]:\n    - [1]: 1"),
+            (
+                json!([[{"a": 1}, {"a": 2}]]),
+                "items[1]:\n  - [2]{a}:\n    1\n    2",
+            ),
+        ];
+
+        let options = EncoderOptions::default();
+        for (array, expected) in cases {
+            for data in [json!({ "items": array.clone() }), array] {
+                let result = encode(&data, &options);
+                if data.is_object() {
+                    assert_eq!(result, expected);
+                }
+                assert_eq!(
+                    crate::decode(&result, &crate::DecoderOptions::default()).unwrap(),
+                    data,
+                    "round trip of {}",
+                    result
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_js_compatible_numbers() {
+        let options = EncoderOptions {
+            js_compatible_numbers: true,
+            ..Default::default()
+        };
+        // Expected strings are the output of `String(x)` in JavaScript
+        let cases = [
+            (0.1 + 0.2, "0.30000000000000004"),
+            (1.5, "1.5"),
+            (100.0, "100"),
+            (-2.5e-8, "-2.5e-8"),
+            (1e-7, "1e-7"),
+            (0.000001, "0.000001"),
+            (1.5e-7, "1.5e-7"),
+            (1e21, "1e+21"),
+            (1.2345678901234568e20, "123456789012345680000"),
+            (1.7976931348623157e308, "1.7976931348623157e+308"),
+            (5e-324, "5e-324"),
+            (-0.0, "0"),
+        ];
+        for (f, expected) in cases {
+            assert_eq!(encode(&json!(f), &options), expected);
+        }
+        assert_eq!(
+            encode(&json!(9007199254740993u64), &options),
+            "9007199254740993"
+        );
+    }
+
+    #[test]
+    fn test_tabular_with_reordered_keys() {
+        // Key sets match, insertion orders differ: the header follows the first object
+        let data = json!({
+            "rows": [
+                {"id": 1, "name": "Alice", "active": true},
+                {"active": false, "name": "Bob", "id": 2}
+            ]
+        });
+        let result = encode(&data, &EncoderOptions::default());
+        assert_eq!(
+            result,
+            "rows[2]{id,name,active}:\n  1,Alice,true\n  2,Bob,false"
+        );
+        assert_eq!(
+            crate::decode(&result, &crate::DecoderOptions::default()).unwrap(),
+            data
+        );
+
+        // Same size but different key sets must not be tabular
+        let data = json!({"rows": [{"a": 1, "b": 2}, {"a": 1, "c": 2}]});
+        let result = encode(&data, &EncoderOptions::default());
+        assert_eq!(result, "rows[2]:\n  - a: 1\n  b: 2\n  - a: 1\n  c: 2");
+    }
+
+    #[test]
+    fn test_tabular_threshold() {
+        let data = json!({
+            "one": [{"id": 1, "name": "Alice"}],
+            "two": [{"id": 1, "name": "Alice"}, {"id": 2, "name": "Bob"}]
+        });
+        let options = EncoderOptions {
+            tabular_threshold: 2,
+            ..Default::default()
+        };
+        let result = encode(&data, &options);
+        assert_eq!(
+            result,
+            "one[1]:\n  - id: 1\n  name: Alice\ntwo[2]{id,name}:\n  1,Alice\n  2,Bob"
+        );
+        assert_eq!(
+            crate::decode(&result, &crate::DecoderOptions::default()).unwrap(),
+            data
+        );
+        assert_eq!(
+            describe(&data, &options),
+            "$.one: list: fewer rows than tabular_threshold\n$.two: tabular: uniform object keys [id,name]"
+        );
+
+        // The default threshold of 1 keeps single-row arrays tabular
+        let result = encode(&data, &EncoderOptions::default());
+        assert!(result.starts_with("one[1]{id,name}:\n  1,Alice\n"));
+    }
+
+    #[test]
+    fn test_control_characters_escaped() {
+        let data = json!({"text": "a\u{1b}[0m\u{0}b"});
+        let result = encode(&data, &EncoderOptions::default());
+        assert_eq!(result, "text: \"a\\u001b[0m\\u0000b\"");
+        assert!(!result.chars().any(|c| c.is_control() && c != '\n'));
+        assert_eq!(
+            crate::decode(&result, &crate::DecoderOptions::default()).unwrap(),
+            data
+        );
+    }
+
+    #[test]
+    fn test_validate_rejects_zero_indent() {
+        assert_eq!(EncoderOptions::default().validate(), Ok(()));
+        let options = EncoderOptions {
+            indent: IndentStyle::Spaces(0),
+            ..Default::default()
+        };
+        assert!(options.validate().is_err());
+
+        // Flat output never indents, so it is unaffected
+        assert_eq!(encode(&json!({"a": 1}), &options), "a: 1");
+    }
+
+    #[test]
+    #[cfg(debug_assertions)]
+    #[should_panic(expected = "non-zero indent")]
+    fn test_zero_indent_nested_panics_in_debug() {
+        let options = EncoderOptions {
+            indent: IndentStyle::Spaces(0),
+            ..Default::default()
+        };
+        encode(&json!({"a": {"b": 1}}), &options);
+    }
+
+    #[test]
+    fn test_default_spec_version() {
+        assert_eq!(EncoderOptions::default().spec_version, SpecVersion::V2_0);
+        assert_eq!(
+            crate::DecoderOptions::default().spec_version,
+            SpecVersion::V2_0
+        );
+        assert_eq!(SpecVersion::default().as_str(), "2.0");
+    }
+
+    #[test]
+    fn test_escape_unicode() {
+        let options = EncoderOptions {
+            escape_unicode: true,
+            ..Default::default()
+        };
+        let data = json!({"greeting": "こんにちは", "emoji": "hi 😀", "名前": "ok"});
+        let result = encode(&data, &options);
+        assert_eq!(
+            result,
+            "greeting: \"\\u3053\\u3093\\u306b\\u3061\\u306f\"\n\
+             emoji: \"hi \\ud83d\\ude00\"\n\
+             \"\\u540d\\u524d\": ok"
+        );
+        assert!(result.is_ascii());
+        assert_eq!(
+            crate::decode(&result, &crate::DecoderOptions::default()).unwrap(),
+            data
+        );
+    }
+
+    #[test]
+    fn test_skip_nulls() {
+        let options = EncoderOptions {
+            skip_nulls: true,
+            ..Default::default()
+        };
+        assert_eq!(encode(&json!({"a": 1, "b": null}), &options), "a: 1");
+
+        // A field that is null in every row leaves the tabular header
+        let data = json!({"rows": [{"id": 1, "x": null}, {"id": 2, "x": null}]});
+        assert_eq!(encode(&data, &options), "rows[2]{id}:\n  1\n  2");
+
+        // Nulls in different fields break uniformity, so rows become list items
+        let data = json!({"rows": [{"id": 1, "x": null}, {"id": null, "x": 2}]});
+        assert_eq!(encode(&data, &options), "rows[2]:\n  - id: 1\n  - x: 2");
+
+        // Nulls inside arrays are values, not fields, and are kept
+        let data = json!({"v": [1, null]});
+        assert_eq!(
+            encode(&data, &options),
+            encode(&data, &EncoderOptions::default())
+        );
+    }
+
+    #[test]
+    fn test_single_field_objects_have_no_stray_newlines() {
+        let options = EncoderOptions::default();
+        assert_eq!(encode(&json!({"a": 1}), &options), "a: 1");
+        assert_eq!(encode(&json!({"a": {"b": 1}}), &options), "a:\n  b: 1");
+        assert_eq!(
+            encode(&json!({"a": {"b": {"c": 1}}}), &options),
+            "a:\n  b:\n    c: 1"
+        );
+        assert_eq!(
+            encode(&json!({"a": {"b": 1}, "c": 2}), &options),
+            "a:\n  b: 1\nc: 2"
+        );
+        assert_eq!(
+            encode(&json!([{"a": {"b": 1}}]), &options),
+            "[1]:\n  - a:\n      b: 1"
+        );
+    }
+
+    #[test]
+    fn test_numbers_never_have_leading_plus() {
+        let numbers = json!([
+            0,
+            5,
+            -5,
+            0.5,
+            1e21,
+            1e-7,
+            6.022e23,
+            1.5e300,
+            -2.5e-300,
+            u64::MAX
+        ]);
+        for options in [
+            EncoderOptions::default(),
+            EncoderOptions {
+                preserve_scientific: true,
+                ..Default::default()
+            },
+            EncoderOptions {
+                js_compatible_numbers: true,
+                ..Default::default()
+            },
+        ] {
+            let encoder = Encoder::new(&options, String::new());
+            for n in numbers.as_array().unwrap() {
+                let text = encoder.quote_primitive(n, Delimiter::Comma);
+                assert!(!text.starts_with('+'), "{} -> {}", n, text);
+            }
+        }
+    }
+
+    #[test]
+    fn test_compact_preset() {
+        let data = json!({
+            "service": {"name": "api", "replicas": 3, "env": {"region": "us-east", "debug": false}},
+            "users": [{"id": 1, "name": "Alice"}, {"id": 2, "name": "Bob"}],
+            "tags": ["a", "b"],
+            "items": [{"id": 1, "meta": {"x": 1}}, [1, 2], "text"]
+        });
+        let compact = encode(&data, &EncoderOptions::compact());
+        let default = encode(&data, &EncoderOptions::default());
+        assert!(compact.len() < default.len());
+        assert!(EncoderOptions::compact().validate().is_ok());
+
+        let decode_options = crate::DecoderOptions {
+            indent: IndentStyle::Spaces(1),
+            ..Default::default()
+        };
+        assert_eq!(crate::decode(&compact, &decode_options).unwrap(), data);
+        assert_eq!(
+            crate::decode(&compact, &crate::DecoderOptions::lenient()).unwrap(),
+            data
+        );
+    }
+
+    #[test]
+    fn test_encode_cow() {
+        let options = EncoderOptions::default();
+        for value in [
+            json!(true),
+            json!(false),
+            json!(null),
+            json!("plain"),
+            json!({}),
+        ] {
+            let encoded = encode_cow(&value, &options);
+            assert!(matches!(encoded, Cow::Borrowed(_)), "{}", value);
+            assert_eq!(encoded, encode(&value, &options));
+        }
+
+        for value in [
+            json!({"a": 1}),
+            json!([1, 2]),
+            json!(42),
+            json!("needs: quoting"),
+            json!("true"),
+        ] {
+            let encoded = encode_cow(&value, &options);
+            assert!(matches!(encoded, Cow::Owned(_)), "{}", value);
+            assert_eq!(encoded, encode(&value, &options));
+        }
+
+        let options = EncoderOptions {
+            trailing_newline: true,
+            ..Default::default()
+        };
+        assert_eq!(encode_cow(&json!(true), &options), "true\n");
+        assert_eq!(encode_cow(&json!({}), &options), "");
+    }
+
+    #[test]
+    fn test_del_and_c1_controls_round_trip() {
+        let data = json!({"v": ["a\u{7f}b", "c\u{85}d"]});
+        let result = encode(&data, &EncoderOptions::default());
+        assert_eq!(result, "v[2]: \"a\\u007fb\",\"c\\u0085d\"");
+        assert_eq!(
+            crate::decode(&result, &crate::DecoderOptions::default()).unwrap(),
+            data
+        );
+    }
+
+    #[test]
+    fn test_key_case() {
+        let camel = EncoderOptions {
+            key_case: Some(KeyCase::Camel),
+            ..Default::default()
+        };
+        assert_eq!(encode(&json!({"user_name": 1}), &camel), "userName: 1");
+
+        let data = json!({
+            "user_name": {"HTTPServer": true},
+            "rows": [{"first-name": "A", "zip2Code": 1}]
+        });
+        assert_eq!(
+            encode(&data, &camel),
+            "userName:\n  httpServer: true\nrows[1]{firstName,zip2Code}:\n  A,1"
+        );
+
+        let snake = EncoderOptions {
+            key_case: Some(KeyCase::Snake),
+            ..Default::default()
+        };
+        assert_eq!(
+            encode(&data, &snake),
+            "user_name:\n  http_server: true\nrows[1]{first_name,zip2_code}:\n  A,1"
+        );
+
+        let kebab = EncoderOptions {
+            key_case: Some(KeyCase::Kebab),
+            ..Default::default()
+        };
+        assert_eq!(
+            encode(&data, &kebab),
+            "\"user-name\":\n  \"http-server\": true\nrows[1]{\"first-name\",\"zip2-code\"}:\n  A,1"
+        );
+
+        // Keys without words are left alone
+        assert_eq!(KeyCase::Camel.apply("__"), "__");
+    }
+
+    #[test]
+    fn test_space_after_colon_disabled() {
+        let data = json!({
+            "name": "Alice",
+            "tags": ["a", "b", "c"],
+            "nested": {"n": 1, "items": [[1, 2], {"k": "v"}]},
+            "rows": [{"id": 1}, {"id": 2}]
+        });
+        let tight = EncoderOptions {
+            space_after_colon: false,
+            ..Default::default()
+        };
+        let result = encode(&data, &tight);
+        assert_eq!(
+            result,
+            "name:Alice\ntags[3]:a,b,c\nnested:\n  n:1\n  items[2]:\n    - [2]:1,2\n    - k:v\n\
+             rows[2]{id}:\n  1\n  2"
+        );
+        assert!(result.len() < encode(&data, &EncoderOptions::default()).len());
+        assert_eq!(
+            crate::decode(&result, &crate::DecoderOptions::default()).unwrap(),
+            data
+        );
+
+        let text = json!({"query": "a\nb"});
+        let block = EncoderOptions {
+            multiline_strings: true,
+            ..tight
+        };
+        let result = encode(&text, &block);
+        assert_eq!(result, "query:|\n  a\n  b");
+        assert_eq!(
+            crate::decode(&result, &crate::DecoderOptions::default()).unwrap(),
+            text
+        );
+    }
+
+    #[test]
+    fn test_base64_keys() {
+        let data = json!({
+            "data": "SGVsbG8sIFRPT04h",
+            "note": "SGVsbG8sIFRPT04h",
+            "files": [
+                {"name": "a", "data": "AAEC/w=="},
+                {"name": "b", "data": "not base64!"}
+            ]
+        });
+        let options = EncoderOptions {
+            quote_all_strings: true,
+            base64_keys: ["data".to_string()].into(),
+            ..Default::default()
+        };
+        let result = encode(&data, &options);
+        assert_eq!(
+            result,
+            "data: SGVsbG8sIFRPT04h\nnote: \"SGVsbG8sIFRPT04h\"\n\
+             files[2]{name,data}:\n  \"a\",AAEC/w==\n  \"b\",\"not base64!\""
+        );
+        assert_eq!(
+            crate::decode(&result, &crate::DecoderOptions::default()).unwrap(),
+            data
+        );
+
+        // Base64 that would read back as a number or keyword stays quoted
+        let ambiguous = json!({"data": "1234", "list": [{"data": "null"}]});
+        let result = encode(&ambiguous, &options);
+        assert_eq!(result, "data: \"1234\"\nlist[1]{data}:\n  \"null\"");
+
+        assert!(is_base64("QQ=="));
+        assert!(!is_base64("QQ="));
+        assert!(!is_base64("Q=Q="));
+        assert!(!is_base64(""));
+    }
+
+    #[test]
+    fn test_max_inline_array_len() {
+        let options = EncoderOptions {
+            max_inline_array_len: Some(3),
+            ..Default::default()
+        };
+        let cases = [
+            (json!({"tags": ["a", "b", "c"]}), "tags[3]: a,b,c"),
+            (
+                json!({"tags": ["a", "b", "c", "d"]}),
+                "tags[4]:\n  - a\n  - b\n  - c\n  - d",
+            ),
+            (json!([1, 2, 3]), "[3]: 1,2,3"),
+            (json!([1, 2, 3, 4]), "[4]:\n  - 1\n  - 2\n  - 3\n  - 4"),
+        ];
+        for (data, expected) in cases {
+            let result = encode(&data, &options);
+            assert_eq!(result, expected);
+            assert_eq!(
+                crate::decode(&result, &crate::DecoderOptions::default()).unwrap(),
+                data
+            );
+        }
+
+        let description = describe(&json!({"tags": [1, 2, 3, 4]}), &options);
+        assert_eq!(
+            description,
+            "$.tags: list: more elements than max_inline_array_len"
+        );
+    }
+
+    #[test]
+    fn test_key_order() {
+        let data = json!({
+            "name": "Alice",
+            "zone": "eu",
+            "id": 1,
+            "users": [
+                {"name": "Bob", "id": 2, "age": 30},
+                {"name": "Carol", "id": 3, "age": 40}
+            ]
+        });
+        let options = EncoderOptions {
+            key_order: Some(vec!["id".to_string(), "missing".to_string()]),
+            ..Default::default()
+        };
+        let result = encode(&data, &options);
+        assert_eq!(
+            result,
+            "id: 1\nname: Alice\nzone: eu\nusers[2]{id,name,age}:\n  2,Bob,30\n  3,Carol,40"
+        );
+
+        // Unlisted keys follow in sorted order with `sort_keys`
+        let sorted = EncoderOptions {
+            sort_keys: true,
+            ..options
+        };
+        let result = encode(&data, &sorted);
+        assert_eq!(
+            result,
+            "id: 1\nname: Alice\nusers[2]{id,age,name}:\n  2,30,Bob\n  3,40,Carol\nzone: eu"
+        );
+        assert_eq!(crate::to_string(&data, &sorted).unwrap(), result);
+    }
+
+    #[test]
+    fn test_tabular_union_keys() {
+        let data = json!({"users": [
+            {"id": 1, "name": "Alice", "email": "a@x.io"},
+            {"id": 2, "name": "Bob"},
+            {"id": 3, "email": "c@x.io", "name": null}
+        ]});
+        let options = EncoderOptions {
+            tabular_union_keys: true,
+            ..Default::default()
+        };
+        let result = encode(&data, &options);
+        assert_eq!(
+            result,
+            "users[3]{id,name,email}:\n  1,Alice,a@x.io\n  2,Bob,null\n  3,null,c@x.io"
+        );
+        assert!(encode(&data, &EncoderOptions::default()).starts_with("users[3]:\n  - id: 1"));
+        assert_eq!(
+            crate::decode(&result, &crate::DecoderOptions::default()).unwrap(),
+            json!({"users": [
+                {"id": 1, "name": "Alice", "email": "a@x.io"},
+                {"id": 2, "name": "Bob", "email": null},
+                {"id": 3, "name": null, "email": "c@x.io"}
+            ]})
+        );
+
+        // Missing leading or trailing cells survive tab delimiters
+        let data = json!([{"a": 1, "b": 2}, {"b": 3}, {"a": 4}]);
+        let tabs = EncoderOptions {
+            delimiter: Delimiter::Tab,
+            align_columns: true,
+            ..options.clone()
+        };
+        let result = encode(&data, &tabs);
+        assert_eq!(result, "[3\t]{a\tb}:\n  1   \t2\n  null\t3\n  4   \tnull");
+        assert_eq!(
+            crate::decode(&result, &crate::DecoderOptions::default()).unwrap(),
+            json!([{"a": 1, "b": 2}, {"a": null, "b": 3}, {"a": 4, "b": null}])
+        );
+
+        // Nested values and empty objects keep the list form
+        for data in [json!([{"a": 1}, {"b": [1]}]), json!([{"a": 1}, {}])] {
+            assert!(encode(&data, &options).starts_with("[2]:\n  - "));
+        }
+    }
+
+    #[test]
+    fn test_inline_small_objects() {
+        let data = json!({
+            "point": {"x": 1, "y": -2.5},
+            "user": {"name": "Doe, Jane", "tag": "{x}", "a b": null},
+            "big": {"a": 1, "b": 2, "c": 3},
+            "deep": {"inner": {"k": true}},
+            "items": [{"id": 1, "pos": {"x": 0, "y": 0}}]
+        });
+        let options = EncoderOptions {
+            inline_small_objects: Some(3),
+            ..Default::default()
+        };
+        let result = encode(&data, &options);
+        assert_eq!(
+            result,
+            "point: {x: 1, y: -2.5}\n\
+             user:\n  name: \"Doe, Jane\"\n  tag: \"{x}\"\n  \"a b\": null\n\
+             big:\n  a: 1\n  b: 2\n  c: 3\n\
+             deep:\n  inner: {k: true}\n\
+             items[1]:\n  - id: 1\n  pos: {x: 0, y: 0}"
+        );
+        let decode_options = crate::DecoderOptions {
+            inline_objects: true,
+            ..Default::default()
+        };
+        assert_eq!(crate::decode(&result, &decode_options).unwrap(), data);
+
+        let two = json!({"a": {"name": "Doe, Jane", "tag": "{x}"}});
+        let tight = EncoderOptions {
+            space_after_colon: false,
+            ..options.clone()
+        };
+        let result = encode(&two, &tight);
+        assert_eq!(result, "a:{name:\"Doe, Jane\",tag:\"{x}\"}");
+        assert_eq!(crate::decode(&result, &decode_options).unwrap(), two);
+        assert_eq!(crate::to_string(&two, &tight).unwrap(), result);
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn test_encode_array_streaming() {
+        /// Counts bytes without keeping them
+        struct CountingSink(usize);
+
+        impl io::Write for CountingSink {
+            fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+                self.0 += buf.len();
+                Ok(buf.len())
+            }
+
+            fn flush(&mut self) -> io::Result<()> {
+                Ok(())
+            }
+        }
+
+        let fields = ["id".to_string(), "tag".to_string()];
+        let rows = (0..100_000).map(|id| json!({"id": id, "tag": "x"}));
+        let mut sink = CountingSink(0);
+        encode_array_streaming(rows, &fields, Delimiter::Comma, &mut sink).unwrap();
+
+        // Header, then `\n  <id>,x` per row
+        let digits: usize = (0..100_000).map(|id: u32| id.to_string().len()).sum();
+        assert_eq!(sink.0, "[]{id,tag}:".len() + 100_000 * 5 + digits);
+
+        let rows = vec![json!({"id": 1, "tag": "a|b"}), json!({"id": 2})];
+        let mut out = Vec::new();
+        encode_array_streaming(rows, &fields, Delimiter::Pipe, &mut out).unwrap();
+        let toon = String::from_utf8(out).unwrap();
+        assert_eq!(toon, "[|]{id|tag}:\n  1|\"a|b\"\n  2|null");
+        assert_eq!(
+            crate::decode(&toon, &crate::DecoderOptions::default()).unwrap(),
+            json!([{"id": 1, "tag": "a|b"}, {"id": 2, "tag": null}])
+        );
+
+        for rows in [vec![json!(1)], vec![json!({"id": [1]})]] {
+            let result = encode_array_streaming(rows, &fields, Delimiter::Comma, &mut Vec::new());
+            assert!(matches!(result, Err(EncodeError::Serialize(_))));
+        }
+    }
+
+    #[test]
+    fn test_array_on_same_line() {
+        let options = EncoderOptions {
+            array_on_same_line: true,
+            ..Default::default()
+        };
+        let cases = [
+            (
+                json!({"items": [{"a": 1, "b": [1, 2]}]}),
+                "items[1]: - a: 1\n  b[2]: 1,2",
+            ),
+            (
+                json!({"x": {"items": [{"a": {"c": 1}, "b": "y"}]}}),
+                "x:\n  items[1]: - a:\n        c: 1\n    b: y",
+            ),
+            (json!([[{"k": 1}, 3]]), "[1]: - [2]:\n    - k: 1\n    - 3"),
+            // Tabular, inline and longer arrays are unaffected
+            (json!({"rows": [{"a": 1}]}), "rows[1]{a}:\n  1"),
+            (json!({"tags": ["a"]}), "tags[1]: a"),
+            (json!({"mixed": [1, "a"]}), "mixed[2]:\n  - 1\n  - a"),
+        ];
+        let decode_options = crate::DecoderOptions {
+            same_line_list_items: true,
+            ..Default::default()
+        };
+        for (data, expected) in cases {
+            let result = encode(&data, &options);
+            assert_eq!(result, expected);
+            assert_eq!(crate::decode(&result, &decode_options).unwrap(), data);
+        }
+
+        // Without the decoder option the item is read as an inline value
+        assert_eq!(
+            crate::decode("x[1]: - hello", &crate::DecoderOptions::default()).unwrap(),
+            json!({"x": ["- hello"]})
+        );
+    }
+
+    #[test]
+    fn test_unquote_iso_dates() {
+        let options = EncoderOptions {
+            unquote_iso_dates: true,
+            ..Default::default()
+        };
+        let data = json!({
+            "at": "2025-01-01T10:00:00Z",
+            "local": "2025-01-01T10:00:00.250+01:00",
+            "times": ["2025-01-01T10:00:00", "x"],
+            "rows": [{"t": "2025-01-01T10:00:00-05:00", "n": 1}],
+            "events": ["2025-01-01T10:00:00Z", {"k": 1}],
+            "partial": "2025-01-01T10:00",
+            "date": "2025-01-01",
+        });
+        let result = encode(&data, &options);
+        assert_eq!(
+            result,
+            "at: 2025-01-01T10:00:00Z\n\
+             local: 2025-01-01T10:00:00.250+01:00\n\
+             times[2]: 2025-01-01T10:00:00,x\n\
+             rows[1]{t,n}:\n  2025-01-01T10:00:00-05:00,1\n\
+             events[2]:\n  - \"2025-01-01T10:00:00Z\"\n  - k: 1\n\
+             partial: \"2025-01-01T10:00\"\n\
+             date: 2025-01-01"
+        );
+        assert_eq!(
+            crate::decode(&result, &crate::DecoderOptions::default()).unwrap(),
+            data
+        );
+
+        // Root strings and the default keep the quotes
+        let ts = json!("2025-01-01T10:00:00Z");
+        assert_eq!(encode(&ts, &options), "\"2025-01-01T10:00:00Z\"");
+        let result = encode(&data, &EncoderOptions::default());
+        assert!(result.starts_with("at: \"2025-01-01T10:00:00Z\""));
+        assert_eq!(
+            crate::decode(&result, &crate::DecoderOptions::default()).unwrap(),
+            data
+        );
+
+        for s in [
+            "2025-01-01T10:00:00.Z",
+            "2025-01-01T10:00:00+0100",
+            "2025-01-01 10:00:00",
+            "2025-01-01T10:00:00Zx",
+        ] {
+            assert!(!is_iso_timestamp(s), "{}", s);
+        }
+    }
+
+    #[test]
+    fn test_redact_keys() {
+        let options = EncoderOptions {
+            redact_keys: ["password", "token"]
+                .iter()
+                .map(|k| k.to_string())
+                .collect(),
+            ..Default::default()
+        };
+        assert_eq!(
+            encode(&json!({"password": "secret"}), &options),
+            "password: \"***\""
+        );
+
+        let data = json!({
+            "user": {"name": "Alice", "password": 1234, "token": {"id": "t1"}},
+            "sessions": [{"id": 1, "token": "abc"}, {"id": 2, "token": null}],
+            "items": [{"token": ["x"], "b": 1}],
+        });
+        let result = encode(&data, &options);
+        assert_eq!(
+            result,
+            "user:\n  name: Alice\n  password: \"***\"\n  token: \"***\"\n\
+             sessions[2]{id,token}:\n  1,\"***\"\n  2,\"***\"\n\
+             items[1]:\n  - token: \"***\"\n  b: 1"
+        );
+        assert_eq!(crate::to_string(&data, &options).unwrap(), result);
+
+        let inline = EncoderOptions {
+            inline_small_objects: Some(3),
+            ..options.clone()
+        };
+        assert_eq!(
+            encode(&json!({"login": {"user": "a", "password": "b"}}), &inline),
+            "login: {user: a, password: \"***\"}"
+        );
+    }
 }
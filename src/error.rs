@@ -1,16 +1,32 @@
-//! Custom error types for the decoder.
+//! Custom error types for the encoder and decoder.
 
-use std::fmt;
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+use core::fmt;
+#[cfg(feature = "std")]
+use std::io;
 
 /// An error that can occur during the decoding of a TOON string.
+///
+/// Every variant produced while parsing carries the 1-based `line` of the
+/// input where the problem was detected, available uniformly through
+/// [`DecodeError::line`].
 #[derive(Debug, Clone, PartialEq)]
 pub enum DecodeError {
     /// The input string contains invalid indentation at the given line.
     InvalidIndentation { line: usize },
+    /// A line indented with spaces also has tabs in its leading whitespace.
+    MixedIndentation { line: usize },
     /// An array header has an invalid format.
-    InvalidArrayHeader(String),
+    InvalidArrayHeader { line: usize, message: String },
     /// An array's actual length does not match its declared length.
-    ArrayLengthMismatch { expected: usize, found: usize },
+    ///
+    /// `line` refers to the line holding the array header.
+    ArrayLengthMismatch {
+        line: usize,
+        expected: usize,
+        found: usize,
+    },
     /// A row in a tabular array has a different number of columns than the header.
     RowWidthMismatch {
         line: usize,
@@ -22,7 +38,173 @@ pub enum DecodeError {
     /// An invalid escape sequence was found in a string.
     InvalidEscapeSequence { line: usize, sequence: String },
     /// A generic parsing error.
-    ParseError(String),
+    ParseError { line: usize, message: String },
+    /// An object contains the same key more than once (strict mode only).
+    DuplicateKey { line: usize, key: String },
+    /// An unquoted value contains a raw control character such as a tab (strict mode only).
+    InvalidCharacter { line: usize, ch: char },
+    /// The decoded value could not be deserialized into the requested type.
+    Deserialize(String),
+    /// Reading the input failed (including input that is not valid UTF-8).
+    Io(String),
+    /// Several errors, ordered by line, from [`decode_checked`](crate::decode_checked).
+    Multiple(Vec<DecodeError>),
+}
+
+/// A problem tolerated while decoding with `strict` off or `ragged_rows` on
+///
+/// Produced by [`decode_with_warnings`](crate::decode_with_warnings). Like
+/// [`DecodeError`], each warning carries the 1-based `line` it refers to.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum Warning {
+    /// An array's item count differs from its declared length.
+    ///
+    /// `line` refers to the line holding the array header.
+    ArrayLengthMismatch {
+        line: usize,
+        expected: usize,
+        found: usize,
+    },
+    /// A tabular row has a different number of cells than the header.
+    RowWidthMismatch {
+        line: usize,
+        expected: usize,
+        found: usize,
+    },
+}
+
+impl fmt::Display for Warning {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Warning::ArrayLengthMismatch {
+                line,
+                expected,
+                found,
+            } => write!(
+                f,
+                "Array length mismatch at line {}: expected {}, got {}",
+                line, expected, found
+            ),
+            Warning::RowWidthMismatch {
+                line,
+                expected,
+                found,
+            } => write!(
+                f,
+                "Row width mismatch at line {}: expected {} fields, got {}",
+                line, expected, found
+            ),
+        }
+    }
+}
+
+/// A byte range in the decoded input that an error refers to
+///
+/// `&input[span.start..span.end]` is the offending text. Produced by
+/// [`decode_with_span`](crate::decode_with_span).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ErrorSpan {
+    /// Byte offset of the first byte of the offending text
+    pub start: usize,
+    /// Byte offset just past the offending text
+    pub end: usize,
+}
+
+/// Category of a [`DecodeError`], for logging and metrics
+///
+/// Unlike the error's `Display` text, the kinds and their [`ErrorKind::as_str`]
+/// codes are stable.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum ErrorKind {
+    /// Invalid or mixed indentation
+    Indentation,
+    /// A malformed array header
+    ArrayHeader,
+    /// An array whose item count differs from its declared length
+    LengthMismatch,
+    /// A tabular row with the wrong number of cells
+    RowWidth,
+    /// A line that is neither a field, a list item nor a row
+    Line,
+    /// An invalid escape sequence in a quoted string
+    Escape,
+    /// A raw control character in an unquoted value
+    Character,
+    /// Any other parse failure
+    Parse,
+    /// A repeated object key
+    DuplicateKey,
+    /// A decoded value that does not fit the requested Rust type
+    Deserialize,
+    /// A failure reading the input
+    Io,
+    /// Several errors reported together
+    Multiple,
+}
+
+impl ErrorKind {
+    /// A stable snake_case code for the kind, e.g. `"length_mismatch"`
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ErrorKind::Indentation => "indentation",
+            ErrorKind::ArrayHeader => "array_header",
+            ErrorKind::LengthMismatch => "length_mismatch",
+            ErrorKind::RowWidth => "row_width",
+            ErrorKind::Line => "line",
+            ErrorKind::Escape => "escape",
+            ErrorKind::Character => "character",
+            ErrorKind::Parse => "parse",
+            ErrorKind::DuplicateKey => "duplicate_key",
+            ErrorKind::Deserialize => "deserialize",
+            ErrorKind::Io => "io",
+            ErrorKind::Multiple => "multiple",
+        }
+    }
+}
+
+impl DecodeError {
+    /// Returns the category of the error
+    pub fn kind(&self) -> ErrorKind {
+        match self {
+            DecodeError::InvalidIndentation { .. } | DecodeError::MixedIndentation { .. } => {
+                ErrorKind::Indentation
+            }
+            DecodeError::InvalidArrayHeader { .. } => ErrorKind::ArrayHeader,
+            DecodeError::ArrayLengthMismatch { .. } => ErrorKind::LengthMismatch,
+            DecodeError::RowWidthMismatch { .. } => ErrorKind::RowWidth,
+            DecodeError::InvalidLine { .. } => ErrorKind::Line,
+            DecodeError::InvalidEscapeSequence { .. } => ErrorKind::Escape,
+            DecodeError::InvalidCharacter { .. } => ErrorKind::Character,
+            DecodeError::ParseError { .. } => ErrorKind::Parse,
+            DecodeError::DuplicateKey { .. } => ErrorKind::DuplicateKey,
+            DecodeError::Deserialize(_) => ErrorKind::Deserialize,
+            DecodeError::Io(_) => ErrorKind::Io,
+            DecodeError::Multiple(_) => ErrorKind::Multiple,
+        }
+    }
+
+    /// Returns the 1-based line number where the error was detected, if the
+    /// error is tied to a location in the input
+    ///
+    /// For [`DecodeError::Multiple`] this is the line of the first error.
+    pub fn line(&self) -> Option<usize> {
+        match self {
+            DecodeError::InvalidIndentation { line }
+            | DecodeError::MixedIndentation { line }
+            | DecodeError::InvalidArrayHeader { line, .. }
+            | DecodeError::ArrayLengthMismatch { line, .. }
+            | DecodeError::RowWidthMismatch { line, .. }
+            | DecodeError::InvalidLine { line, .. }
+            | DecodeError::InvalidEscapeSequence { line, .. }
+            | DecodeError::ParseError { line, .. }
+            | DecodeError::DuplicateKey { line, .. }
+            | DecodeError::InvalidCharacter { line, .. } => Some(*line),
+            DecodeError::Deserialize(_) | DecodeError::Io(_) => None,
+            DecodeError::Multiple(errors) => errors.iter().find_map(DecodeError::line),
+        }
+    }
 }
 
 impl fmt::Display for DecodeError {
@@ -31,14 +213,21 @@ impl fmt::Display for DecodeError {
             DecodeError::InvalidIndentation { line } => {
                 write!(f, "Invalid indentation at line {}", line)
             }
-            DecodeError::InvalidArrayHeader(msg) => write!(f, "Invalid array header: {}", msg),
-            DecodeError::ArrayLengthMismatch { expected, found } => {
-                write!(
-                    f,
-                    "Array length mismatch: expected {}, got {}",
-                    expected, found
-                )
+            DecodeError::MixedIndentation { line } => {
+                write!(f, "Mixed tabs and spaces in indentation at line {}", line)
             }
+            DecodeError::InvalidArrayHeader { line, message } => {
+                write!(f, "Invalid array header at line {}: {}", line, message)
+            }
+            DecodeError::ArrayLengthMismatch {
+                line,
+                expected,
+                found,
+            } => write!(
+                f,
+                "Array length mismatch at line {}: expected {}, got {}",
+                line, expected, found
+            ),
             DecodeError::RowWidthMismatch {
                 line,
                 expected,
@@ -58,9 +247,166 @@ impl fmt::Display for DecodeError {
                     line, sequence
                 )
             }
-            DecodeError::ParseError(msg) => write!(f, "Parse error: {}", msg),
+            DecodeError::ParseError { line, message } => {
+                write!(f, "Parse error at line {}: {}", line, message)
+            }
+            DecodeError::DuplicateKey { line, key } => {
+                write!(f, "Duplicate key at line {}: {}", line, key)
+            }
+            DecodeError::InvalidCharacter { line, ch } => {
+                write!(
+                    f,
+                    "Invalid character at line {}: {:?} must be escaped in a quoted string",
+                    line, ch
+                )
+            }
+            DecodeError::Deserialize(msg) => write!(f, "Deserialization error: {}", msg),
+            DecodeError::Io(msg) => write!(f, "I/O error: {}", msg),
+            DecodeError::Multiple(errors) => {
+                write!(f, "{} errors", errors.len())?;
+                for err in errors {
+                    write!(f, "\n  {}", err)?;
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
+impl core::error::Error for DecodeError {}
+
+/// An error that can occur while serializing a Rust value to TOON.
+#[derive(Debug, Clone, PartialEq)]
+pub enum EncodeError {
+    /// The value cannot be represented in TOON (e.g. a map with non-string keys).
+    Serialize(String),
+    /// Writing the output failed.
+    Io(String),
+    /// A `NaN` or infinite float, which TOON has no syntax for.
+    ///
+    /// Only returned by [`try_encode`](crate::try_encode); the other encoding
+    /// functions write `null` instead.
+    NonFiniteNumber(f64),
+}
+
+impl fmt::Display for EncodeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            EncodeError::Serialize(msg) => write!(f, "Serialization error: {}", msg),
+            EncodeError::Io(msg) => write!(f, "I/O error: {}", msg),
+            EncodeError::NonFiniteNumber(v) => {
+                write!(f, "Non-finite number {} cannot be represented in TOON", v)
+            }
         }
     }
 }
 
-impl std::error::Error for DecodeError {}
+impl core::error::Error for EncodeError {}
+
+impl serde::ser::Error for EncodeError {
+    fn custom<T: fmt::Display>(msg: T) -> Self {
+        EncodeError::Serialize(msg.to_string())
+    }
+}
+
+#[cfg(feature = "std")]
+impl From<io::Error> for EncodeError {
+    fn from(err: io::Error) -> Self {
+        EncodeError::Io(err.to_string())
+    }
+}
+
+impl From<fmt::Error> for EncodeError {
+    fn from(err: fmt::Error) -> Self {
+        EncodeError::Io(err.to_string())
+    }
+}
+
+impl From<serde_json::Error> for EncodeError {
+    fn from(err: serde_json::Error) -> Self {
+        EncodeError::Serialize(err.to_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_error_kinds() {
+        let message = String::new;
+        let cases = [
+            (
+                DecodeError::InvalidIndentation { line: 1 },
+                ErrorKind::Indentation,
+            ),
+            (
+                DecodeError::MixedIndentation { line: 1 },
+                ErrorKind::Indentation,
+            ),
+            (
+                DecodeError::InvalidArrayHeader {
+                    line: 1,
+                    message: message(),
+                },
+                ErrorKind::ArrayHeader,
+            ),
+            (
+                DecodeError::ArrayLengthMismatch {
+                    line: 1,
+                    expected: 2,
+                    found: 1,
+                },
+                ErrorKind::LengthMismatch,
+            ),
+            (
+                DecodeError::RowWidthMismatch {
+                    line: 1,
+                    expected: 2,
+                    found: 1,
+                },
+                ErrorKind::RowWidth,
+            ),
+            (
+                DecodeError::InvalidLine {
+                    line: 1,
+                    content: message(),
+                },
+                ErrorKind::Line,
+            ),
+            (
+                DecodeError::InvalidEscapeSequence {
+                    line: 1,
+                    sequence: message(),
+                },
+                ErrorKind::Escape,
+            ),
+            (
+                DecodeError::InvalidCharacter { line: 1, ch: '\t' },
+                ErrorKind::Character,
+            ),
+            (
+                DecodeError::ParseError {
+                    line: 1,
+                    message: message(),
+                },
+                ErrorKind::Parse,
+            ),
+            (
+                DecodeError::DuplicateKey {
+                    line: 1,
+                    key: message(),
+                },
+                ErrorKind::DuplicateKey,
+            ),
+            (DecodeError::Deserialize(message()), ErrorKind::Deserialize),
+            (DecodeError::Io(message()), ErrorKind::Io),
+            (DecodeError::Multiple(Vec::new()), ErrorKind::Multiple),
+        ];
+        for (err, kind) in cases {
+            assert_eq!(err.kind(), kind, "{:?}", err);
+        }
+
+        assert_eq!(ErrorKind::LengthMismatch.as_str(), "length_mismatch");
+    }
+}
@@ -8,6 +8,19 @@
 //! This crate provides both encoding (JSON → TOON) and decoding (TOON → JSON) with
 //! full spec compliance, automatic format detection, and configurable options.
 //!
+//! Object keys keep their order through encoding and decoding: the default
+//! `std` feature enables `serde_json`'s `preserve_order` feature, so
+//! [`serde_json::Map`] is insertion-ordered. Without that feature the map would
+//! sort its keys and round trips would reorder fields.
+//!
+//! ## `no_std`
+//!
+//! The `std` feature is on by default. Without it the crate is `#![no_std]`
+//! and needs only `alloc`: encoding, decoding and serde support remain, while
+//! the `std::io` functions (`encode_to_writer`, `decode_reader`, `to_writer`
+//! and `encode_array_streaming`) are left out. Since `serde_json` only
+//! preserves key order with `std`, objects then come back with sorted keys.
+//!
 //! ## Example
 //!
 //! ```rust
@@ -29,13 +42,257 @@
 //! assert_eq!(data, decoded);
 //! ```
 
+#![cfg_attr(not(any(feature = "std", test)), no_std)]
+
+extern crate alloc;
+
 mod common;
+mod de;
 mod decoder;
 mod encoder;
 mod error;
+mod ser;
 
 // Re-export public API
-pub use common::Delimiter;
-pub use decoder::{decode, DecoderOptions};
-pub use encoder::{encode, EncoderOptions};
-pub use error::DecodeError;
+pub use common::{Delimiter, IndentStyle, SpecVersion};
+pub use de::from_str;
+#[cfg(feature = "std")]
+pub use decoder::decode_reader;
+pub use decoder::{
+    decode, decode_checked, decode_many, decode_with_span, decode_with_trace, decode_with_warnings,
+    detect_indent, parse_assignment, validate, ArrayForm, DecodeTrace, DecoderOptions,
+    EscapePolicy, StreamingDecoder, ValueType,
+};
+pub use encoder::{describe, encode, encode_cow, EncoderOptions, KeyCase, ToToon, ToonDisplay};
+#[cfg(feature = "std")]
+pub use encoder::{encode_array_streaming, encode_to_writer};
+pub use error::{DecodeError, EncodeError, ErrorKind, ErrorSpan, Warning};
+#[cfg(feature = "std")]
+pub use ser::to_writer;
+pub use ser::{to_string, try_encode};
+
+use alloc::format;
+use alloc::string::{String, ToString};
+use core::fmt;
+use core::fmt::Write as _;
+use core::str::FromStr;
+use serde_json::Value;
+
+/// Decode TOON and re-encode it, producing a normalized form of the document
+///
+/// Formatting that does not affect the decoded value (indentation, delimiter
+/// choice, quoting, number spelling such as `1.50` or `1e3`) is normalized to
+/// what `encode_options` produces, so equal documents give equal output. Set
+/// [`EncoderOptions::sort_keys`] to also make the result independent of key
+/// order. The output is a fixed point: canonicalizing it again returns it
+/// unchanged.
+///
+/// ```rust
+/// use json2toon_rs::{canonicalize, DecoderOptions, EncoderOptions};
+///
+/// let options = EncoderOptions { sort_keys: true, ..Default::default() };
+/// let a = canonicalize("b: 1.50\na: x", &DecoderOptions::default(), &options).unwrap();
+/// let b = canonicalize("a: \"x\"\nb: 1.5", &DecoderOptions::default(), &options).unwrap();
+/// assert_eq!(a, b);
+/// ```
+pub fn canonicalize(
+    input: &str,
+    decode_options: &DecoderOptions,
+    encode_options: &EncoderOptions,
+) -> Result<String, DecodeError> {
+    decode(input, decode_options).map(|value| encode(&value, encode_options))
+}
+
+/// Which input of [`semantically_equal`] failed to decode
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Side {
+    /// The first document, `a`
+    A,
+    /// The second document, `b`
+    B,
+}
+
+/// Check whether two TOON documents decode to the same value
+///
+/// Formatting that does not affect the decoded value, such as delimiter
+/// choice, quoting, indentation width or number spelling, is ignored. As with
+/// [`Value`] equality, object key order is ignored too. If either document
+/// fails to decode, the error is returned together with the [`Side`] it came
+/// from; `a` is decoded first.
+///
+/// ```rust
+/// use json2toon_rs::{semantically_equal, DecoderOptions, Side};
+///
+/// let options = DecoderOptions::default();
+/// assert!(semantically_equal("tags[2]: a,b", "tags[2|]: \"a\"|b", &options).unwrap());
+/// assert!(!semantically_equal("n: 1", "n: 2", &options).unwrap());
+/// assert!(semantically_equal("a: 1\nb: 2", "b: 2\na: 1", &options).unwrap());
+///
+/// let (side, _) = semantically_equal("n: 1", "n[2]: 1", &options).unwrap_err();
+/// assert_eq!(side, Side::B);
+/// ```
+pub fn semantically_equal(
+    a: &str,
+    b: &str,
+    options: &DecoderOptions,
+) -> Result<bool, (Side, DecodeError)> {
+    let a = decode(a, options).map_err(|err| (Side::A, err))?;
+    let b = decode(b, options).map_err(|err| (Side::B, err))?;
+    Ok(a == b)
+}
+
+/// Encode and decode a value, describing the first place the result differs
+///
+/// Returns `Ok(None)` when the round trip reproduces `value`, and otherwise a
+/// message naming the first divergence by its JSON Pointer path, such as
+/// `/items/1/price: expected 1e+19, found 10000000000000000000`. Object key
+/// order is ignored, as with [`Value`] equality. Useful for finding which
+/// part of a large document does not survive a given pair of options.
+///
+/// ```rust
+/// use json2toon_rs::{round_trip_diff, DecoderOptions, EncoderOptions};
+/// use serde_json::json;
+///
+/// let value = json!({"a": {"b": [1, 2.0]}});
+/// let diff = round_trip_diff(&value, &EncoderOptions::default(), &DecoderOptions::default());
+/// assert_eq!(diff.unwrap().unwrap(), "/a/b/1: expected 2.0, found 2");
+/// ```
+pub fn round_trip_diff(
+    value: &Value,
+    encode_options: &EncoderOptions,
+    decode_options: &DecoderOptions,
+) -> Result<Option<String>, DecodeError> {
+    let decoded = decode(&encode(value, encode_options), decode_options)?;
+    Ok(first_difference(value, &decoded, &mut String::new()))
+}
+
+/// Find the first difference between two values, extending `path` (a JSON
+/// Pointer) while descending
+fn first_difference(expected: &Value, found: &Value, path: &mut String) -> Option<String> {
+    let at = |path: &str| {
+        if path.is_empty() {
+            "(root)".to_string()
+        } else {
+            path.to_string()
+        }
+    };
+    match (expected, found) {
+        (Value::Object(a), Value::Object(b)) => {
+            for (key, value) in a {
+                let len = path.len();
+                path.push('/');
+                path.push_str(&key.replace('~', "~0").replace('/', "~1"));
+                let diff = match b.get(key) {
+                    Some(other) => first_difference(value, other, path),
+                    None => Some(format!("{}: missing after round trip", at(path))),
+                };
+                path.truncate(len);
+                if diff.is_some() {
+                    return diff;
+                }
+            }
+            b.keys()
+                .find(|key| !a.contains_key(*key))
+                .map(|key| format!("{}: unexpected key {:?} after round trip", at(path), key))
+        }
+        (Value::Array(a), Value::Array(b)) => {
+            for (i, (value, other)) in a.iter().zip(b).enumerate() {
+                let len = path.len();
+                let _ = write!(path, "/{}", i);
+                let diff = first_difference(value, other, path);
+                path.truncate(len);
+                if diff.is_some() {
+                    return diff;
+                }
+            }
+            (a.len() != b.len()).then(|| {
+                format!(
+                    "{}: expected {} items, found {}",
+                    at(path),
+                    a.len(),
+                    b.len()
+                )
+            })
+        }
+        _ if expected == found => None,
+        _ => Some(format!(
+            "{}: expected {}, found {}",
+            at(path),
+            expected,
+            found
+        )),
+    }
+}
+
+/// Built only without `std`, so `cargo build --no-default-features` checks
+/// that the encoder, decoder and serde support compile with just `alloc`
+#[cfg(not(feature = "std"))]
+#[allow(dead_code)]
+mod no_std_check {
+    use crate::{decode, encode, from_str, to_string, DecodeError, DecoderOptions, EncoderOptions};
+    use crate::{EncodeError, ToonDisplay};
+    use alloc::format;
+    use alloc::string::String;
+    use serde_json::Value;
+
+    fn round_trip(input: &str) -> Result<String, DecodeError> {
+        let value = decode(input, &DecoderOptions::default())?;
+        Ok(encode(&value, &EncoderOptions::default()))
+    }
+
+    fn serde_round_trip(value: &Value) -> Result<Value, EncodeError> {
+        let toon = to_string(value, &EncoderOptions::default())?;
+        from_str(&toon, &DecoderOptions::default())
+            .map_err(|err| EncodeError::Serialize(format!("{}", err)))
+    }
+
+    fn display(value: &Value) -> String {
+        format!("{}", ToonDisplay::new(value, &EncoderOptions::default()))
+    }
+}
+
+/// A JSON value that converts to and from TOON with default options
+///
+/// Parsing uses [`decode`] with [`DecoderOptions::default`], and `Display`
+/// uses [`encode`] with [`EncoderOptions::default`], giving a compact entry
+/// point for the common case.
+///
+/// ```rust
+/// use json2toon_rs::Toon;
+/// use serde_json::{json, Value};
+///
+/// let toon: Toon = "name: Alice\nage: 30".parse().unwrap();
+/// let value: Value = toon.into();
+/// assert_eq!(value, json!({"name": "Alice", "age": 30}));
+///
+/// assert_eq!(Toon::from(value).to_string(), "name: Alice\nage: 30");
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct Toon(pub Value);
+
+impl FromStr for Toon {
+    type Err = DecodeError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        decode(s, &DecoderOptions::default()).map(Toon)
+    }
+}
+
+impl fmt::Display for Toon {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let options = EncoderOptions::default();
+        fmt::Display::fmt(&ToonDisplay::new(&self.0, &options), f)
+    }
+}
+
+impl From<Value> for Toon {
+    fn from(value: Value) -> Self {
+        Toon(value)
+    }
+}
+
+impl From<Toon> for Value {
+    fn from(toon: Toon) -> Self {
+        toon.0
+    }
+}
@@ -0,0 +1,1038 @@
+//! Serialization of Rust types directly to TOON via serde
+
+use crate::encoder::{Encoder, EncoderOptions};
+use crate::error::EncodeError;
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+use core::fmt::Write;
+use serde::ser::{self, Serialize};
+use serde_json::{Map, Value};
+#[cfg(feature = "std")]
+use {crate::encoder::IoWriter, std::io};
+
+/// Serialize any type implementing `serde::Serialize` to a TOON string
+///
+/// Output is identical to converting with `serde_json::to_value` and calling
+/// [`encode`](crate::encode), without building the intermediate value for
+/// structs and maps: their fields are written as they are serialized. Arrays
+/// are buffered, since choosing between the tabular, inline and list forms
+/// requires seeing every element.
+///
+/// ```rust
+/// use json2toon_rs::{to_string, EncoderOptions};
+/// use serde::Serialize;
+///
+/// #[derive(Serialize)]
+/// struct User {
+///     id: u32,
+///     name: String,
+/// }
+///
+/// let users = vec![
+///     User { id: 1, name: "Alice".to_string() },
+///     User { id: 2, name: "Bob".to_string() },
+/// ];
+/// let toon = to_string(&users, &EncoderOptions::default()).unwrap();
+/// assert_eq!(toon, "[2]{id,name}:\n  1,Alice\n  2,Bob");
+/// ```
+pub fn to_string<T: Serialize + ?Sized>(
+    value: &T,
+    options: &EncoderOptions,
+) -> Result<String, EncodeError> {
+    let mut out = String::new();
+    serialize_into(value, options, &mut out)?;
+    Ok(out)
+}
+
+/// Serialize any type implementing `serde::Serialize` as TOON into `writer`
+///
+/// See [`to_string`]. As with [`encode_to_writer`](crate::encode_to_writer),
+/// wrap unbuffered sinks in a `std::io::BufWriter`.
+#[cfg(feature = "std")]
+pub fn to_writer<T: Serialize + ?Sized, W: io::Write>(
+    value: &T,
+    options: &EncoderOptions,
+    writer: &mut W,
+) -> Result<(), EncodeError> {
+    let mut out = IoWriter::new(writer);
+    serialize_into(value, options, &mut out)
+        .map_err(|err| out.take_error().map_or(err, EncodeError::from))
+}
+
+/// Serialize a value as TOON into any `fmt::Write` sink
+fn serialize_into<T: Serialize + ?Sized, W: Write>(
+    value: &T,
+    options: &EncoderOptions,
+    out: W,
+) -> Result<(), EncodeError> {
+    let mut encoder = Encoder::new(options, out);
+    value.serialize(Serializer {
+        encoder: &mut encoder,
+        slot: Slot::Root,
+    })?;
+    encoder.finish()?;
+    Ok(())
+}
+
+/// Serialize a value to TOON, rejecting data that TOON cannot represent
+///
+/// [`encode`](crate::encode) and [`to_string`] are lossy: a `NaN` or infinite
+/// float becomes `null`, as it does with `serde_json::to_value`. This checks
+/// the whole value first and returns [`EncodeError::NonFiniteNumber`] for the
+/// first such float instead; otherwise the output matches [`to_string`].
+///
+/// ```rust
+/// use json2toon_rs::{try_encode, EncodeError, EncoderOptions};
+///
+/// let options = EncoderOptions::default();
+/// assert_eq!(try_encode(&[1.5, 2.0], &options).unwrap(), "[2]: 1.5,2");
+/// assert!(matches!(
+///     try_encode(&[1.5, f64::NAN], &options),
+///     Err(EncodeError::NonFiniteNumber(_))
+/// ));
+/// ```
+pub fn try_encode<T: Serialize + ?Sized>(
+    value: &T,
+    options: &EncoderOptions,
+) -> Result<String, EncodeError> {
+    value.serialize(FiniteCheck)?;
+    to_string(value, options)
+}
+
+/// Struct name under which `serde_json::Number` serializes with the
+/// `arbitrary_precision` feature, as a single field of the same name holding
+/// the digits
+const NUMBER_TOKEN: &str = "$serde_json::private::Number";
+
+/// Where the value being serialized is written
+#[derive(Clone, Copy)]
+enum Slot {
+    /// The document root
+    Root,
+    /// The value of a field whose key, at the given depth, was just written
+    Field(usize),
+}
+
+struct Serializer<'s, 'a, W: Write> {
+    encoder: &'s mut Encoder<'a, W>,
+    slot: Slot,
+}
+
+impl<W: Write> Serializer<'_, '_, W> {
+    /// Write a fully built value into this serializer's slot
+    fn write_value(self, value: Value) -> Result<(), EncodeError> {
+        match self.slot {
+            Slot::Root => self.encoder.encode_value(&value, 0)?,
+            Slot::Field(depth) => self.encoder.encode_field_value(&value, depth)?,
+        }
+        Ok(())
+    }
+}
+
+/// Convert a serialized map key to the string TOON uses as the key
+fn map_key<T: Serialize + ?Sized>(key: &T) -> Result<String, EncodeError> {
+    match serde_json::to_value(key)? {
+        Value::String(s) => Ok(s),
+        Value::Number(n) => Ok(n.to_string()),
+        Value::Bool(b) => Ok(b.to_string()),
+        _ => Err(EncodeError::Serialize(
+            "map key must be a string".to_string(),
+        )),
+    }
+}
+
+impl<'s, 'a, W: Write> ser::Serializer for Serializer<'s, 'a, W> {
+    type Ok = ();
+    type Error = EncodeError;
+
+    type SerializeSeq = SeqBuffer<'s, 'a, W>;
+    type SerializeTuple = SeqBuffer<'s, 'a, W>;
+    type SerializeTupleStruct = SeqBuffer<'s, 'a, W>;
+    type SerializeTupleVariant = VariantSeqBuffer<'s, 'a, W>;
+    type SerializeMap = FieldWriter<'s, 'a, W>;
+    type SerializeStruct = FieldWriter<'s, 'a, W>;
+    type SerializeStructVariant = VariantMapBuffer<'s, 'a, W>;
+
+    fn serialize_bool(self, v: bool) -> Result<(), EncodeError> {
+        self.write_value(Value::Bool(v))
+    }
+
+    fn serialize_i8(self, v: i8) -> Result<(), EncodeError> {
+        self.write_value(Value::from(v))
+    }
+
+    fn serialize_i16(self, v: i16) -> Result<(), EncodeError> {
+        self.write_value(Value::from(v))
+    }
+
+    fn serialize_i32(self, v: i32) -> Result<(), EncodeError> {
+        self.write_value(Value::from(v))
+    }
+
+    fn serialize_i64(self, v: i64) -> Result<(), EncodeError> {
+        self.write_value(Value::from(v))
+    }
+
+    fn serialize_i128(self, v: i128) -> Result<(), EncodeError> {
+        self.write_value(serde_json::to_value(v)?)
+    }
+
+    fn serialize_u8(self, v: u8) -> Result<(), EncodeError> {
+        self.write_value(Value::from(v))
+    }
+
+    fn serialize_u16(self, v: u16) -> Result<(), EncodeError> {
+        self.write_value(Value::from(v))
+    }
+
+    fn serialize_u32(self, v: u32) -> Result<(), EncodeError> {
+        self.write_value(Value::from(v))
+    }
+
+    fn serialize_u64(self, v: u64) -> Result<(), EncodeError> {
+        self.write_value(Value::from(v))
+    }
+
+    fn serialize_u128(self, v: u128) -> Result<(), EncodeError> {
+        self.write_value(serde_json::to_value(v)?)
+    }
+
+    fn serialize_f32(self, v: f32) -> Result<(), EncodeError> {
+        self.write_value(Value::from(v))
+    }
+
+    fn serialize_f64(self, v: f64) -> Result<(), EncodeError> {
+        self.write_value(Value::from(v))
+    }
+
+    fn serialize_char(self, v: char) -> Result<(), EncodeError> {
+        self.write_value(Value::String(v.to_string()))
+    }
+
+    fn serialize_str(self, v: &str) -> Result<(), EncodeError> {
+        self.write_value(Value::String(v.to_string()))
+    }
+
+    fn serialize_bytes(self, v: &[u8]) -> Result<(), EncodeError> {
+        self.write_value(Value::Array(v.iter().map(|&b| Value::from(b)).collect()))
+    }
+
+    fn serialize_none(self) -> Result<(), EncodeError> {
+        self.write_value(Value::Null)
+    }
+
+    fn serialize_some<T: Serialize + ?Sized>(self, value: &T) -> Result<(), EncodeError> {
+        value.serialize(self)
+    }
+
+    fn serialize_unit(self) -> Result<(), EncodeError> {
+        self.write_value(Value::Null)
+    }
+
+    fn serialize_unit_struct(self, _name: &'static str) -> Result<(), EncodeError> {
+        self.write_value(Value::Null)
+    }
+
+    fn serialize_unit_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        variant: &'static str,
+    ) -> Result<(), EncodeError> {
+        self.write_value(Value::String(variant.to_string()))
+    }
+
+    fn serialize_newtype_struct<T: Serialize + ?Sized>(
+        self,
+        _name: &'static str,
+        value: &T,
+    ) -> Result<(), EncodeError> {
+        value.serialize(self)
+    }
+
+    fn serialize_newtype_variant<T: Serialize + ?Sized>(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        variant: &'static str,
+        value: &T,
+    ) -> Result<(), EncodeError> {
+        let mut map = Map::new();
+        map.insert(variant.to_string(), serde_json::to_value(value)?);
+        self.write_value(Value::Object(map))
+    }
+
+    fn serialize_seq(self, len: Option<usize>) -> Result<Self::SerializeSeq, EncodeError> {
+        Ok(SeqBuffer {
+            ser: self,
+            items: Vec::with_capacity(len.unwrap_or(0)),
+        })
+    }
+
+    fn serialize_tuple(self, len: usize) -> Result<Self::SerializeTuple, EncodeError> {
+        self.serialize_seq(Some(len))
+    }
+
+    fn serialize_tuple_struct(
+        self,
+        _name: &'static str,
+        len: usize,
+    ) -> Result<Self::SerializeTupleStruct, EncodeError> {
+        self.serialize_seq(Some(len))
+    }
+
+    fn serialize_tuple_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        variant: &'static str,
+        len: usize,
+    ) -> Result<Self::SerializeTupleVariant, EncodeError> {
+        Ok(VariantSeqBuffer {
+            ser: self,
+            variant,
+            items: Vec::with_capacity(len),
+        })
+    }
+
+    fn serialize_map(self, _len: Option<usize>) -> Result<Self::SerializeMap, EncodeError> {
+        FieldWriter::new(self)
+    }
+
+    fn serialize_struct(
+        self,
+        name: &'static str,
+        _len: usize,
+    ) -> Result<Self::SerializeStruct, EncodeError> {
+        if name == NUMBER_TOKEN {
+            return Ok(FieldWriter::number(self));
+        }
+        FieldWriter::new(self)
+    }
+
+    fn serialize_struct_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        variant: &'static str,
+        _len: usize,
+    ) -> Result<Self::SerializeStructVariant, EncodeError> {
+        Ok(VariantMapBuffer {
+            ser: self,
+            variant,
+            map: Map::new(),
+        })
+    }
+}
+
+/// Buffers sequence elements so the array layout can be chosen at the end
+struct SeqBuffer<'s, 'a, W: Write> {
+    ser: Serializer<'s, 'a, W>,
+    items: Vec<Value>,
+}
+
+impl<W: Write> ser::SerializeSeq for SeqBuffer<'_, '_, W> {
+    type Ok = ();
+    type Error = EncodeError;
+
+    fn serialize_element<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), EncodeError> {
+        self.items.push(serde_json::to_value(value)?);
+        Ok(())
+    }
+
+    fn end(self) -> Result<(), EncodeError> {
+        self.ser.write_value(Value::Array(self.items))
+    }
+}
+
+impl<W: Write> ser::SerializeTuple for SeqBuffer<'_, '_, W> {
+    type Ok = ();
+    type Error = EncodeError;
+
+    fn serialize_element<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), EncodeError> {
+        ser::SerializeSeq::serialize_element(self, value)
+    }
+
+    fn end(self) -> Result<(), EncodeError> {
+        ser::SerializeSeq::end(self)
+    }
+}
+
+impl<W: Write> ser::SerializeTupleStruct for SeqBuffer<'_, '_, W> {
+    type Ok = ();
+    type Error = EncodeError;
+
+    fn serialize_field<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), EncodeError> {
+        ser::SerializeSeq::serialize_element(self, value)
+    }
+
+    fn end(self) -> Result<(), EncodeError> {
+        ser::SerializeSeq::end(self)
+    }
+}
+
+/// Buffers a tuple variant, written as `{variant: [...]}` like serde_json
+struct VariantSeqBuffer<'s, 'a, W: Write> {
+    ser: Serializer<'s, 'a, W>,
+    variant: &'static str,
+    items: Vec<Value>,
+}
+
+impl<W: Write> ser::SerializeTupleVariant for VariantSeqBuffer<'_, '_, W> {
+    type Ok = ();
+    type Error = EncodeError;
+
+    fn serialize_field<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), EncodeError> {
+        self.items.push(serde_json::to_value(value)?);
+        Ok(())
+    }
+
+    fn end(self) -> Result<(), EncodeError> {
+        let mut map = Map::new();
+        map.insert(self.variant.to_string(), Value::Array(self.items));
+        self.ser.write_value(Value::Object(map))
+    }
+}
+
+/// Buffers a struct variant, written as `{variant: {...}}` like serde_json
+struct VariantMapBuffer<'s, 'a, W: Write> {
+    ser: Serializer<'s, 'a, W>,
+    variant: &'static str,
+    map: Map<String, Value>,
+}
+
+impl<W: Write> ser::SerializeStructVariant for VariantMapBuffer<'_, '_, W> {
+    type Ok = ();
+    type Error = EncodeError;
+
+    fn serialize_field<T: Serialize + ?Sized>(
+        &mut self,
+        key: &'static str,
+        value: &T,
+    ) -> Result<(), EncodeError> {
+        self.map
+            .insert(key.to_string(), serde_json::to_value(value)?);
+        Ok(())
+    }
+
+    fn end(self) -> Result<(), EncodeError> {
+        let mut outer = Map::new();
+        outer.insert(self.variant.to_string(), Value::Object(self.map));
+        self.ser.write_value(Value::Object(outer))
+    }
+}
+
+/// Writes struct and map fields as they are serialized
+///
+/// With `sort_keys` or `key_order` the output order is only known once every
+/// field is seen, and with `inline_small_objects` the layout is, so fields are
+/// buffered instead and written at the end.
+struct FieldWriter<'s, 'a, W: Write> {
+    ser: Serializer<'s, 'a, W>,
+    /// Depth of the field keys
+    depth: usize,
+    index: usize,
+    pending_key: Option<String>,
+    sorted: Option<Map<String, Value>>,
+    /// Whether this is serde_json's `arbitrary_precision` number struct
+    number: bool,
+}
+
+impl<'s, 'a, W: Write> FieldWriter<'s, 'a, W> {
+    fn new(ser: Serializer<'s, 'a, W>) -> Result<Self, EncodeError> {
+        let options = ser.encoder.options();
+        let buffered = options.sort_keys
+            || options.key_order.is_some()
+            || options.inline_small_objects.is_some();
+        let sorted = buffered.then(Map::new);
+        let depth = match ser.slot {
+            Slot::Root => 0,
+            Slot::Field(depth) => {
+                // Nested object: key:
+                if sorted.is_none() {
+                    ser.encoder.push(':')?;
+                }
+                depth + 1
+            }
+        };
+
+        Ok(Self {
+            ser,
+            depth,
+            index: 0,
+            pending_key: None,
+            sorted,
+            number: false,
+        })
+    }
+
+    /// Collect the single digits field of a [`NUMBER_TOKEN`] struct
+    fn number(ser: Serializer<'s, 'a, W>) -> Self {
+        Self {
+            ser,
+            depth: 0,
+            index: 0,
+            pending_key: None,
+            sorted: Some(Map::new()),
+            number: true,
+        }
+    }
+
+    fn write_field<T: Serialize + ?Sized>(
+        &mut self,
+        key: String,
+        value: &T,
+    ) -> Result<(), EncodeError> {
+        if let Some(map) = &mut self.sorted {
+            map.insert(key, serde_json::to_value(value)?);
+            return Ok(());
+        }
+
+        if self.ser.encoder.options().skip_nulls {
+            // The key is only written once the value is known not to be null
+            let value = serde_json::to_value(value)?;
+            if value.is_null() {
+                return Ok(());
+            }
+            self.ser
+                .encoder
+                .write_field_key(self.index, &key, self.depth)?;
+            self.index += 1;
+            return Ok(self.ser.encoder.encode_field_value(&value, self.depth)?);
+        }
+
+        self.ser
+            .encoder
+            .write_field_key(self.index, &key, self.depth)?;
+        self.index += 1;
+        if self.ser.encoder.options().redact_keys.contains(&key) {
+            // Written as the marker without serializing the value
+            return Ok(self
+                .ser
+                .encoder
+                .encode_field_value(&Value::Null, self.depth)?);
+        }
+        value.serialize(Serializer {
+            encoder: &mut *self.ser.encoder,
+            slot: Slot::Field(self.depth),
+        })
+    }
+
+    fn finish(self) -> Result<(), EncodeError> {
+        match self.sorted {
+            Some(map) if self.number => {
+                let number = match map.get(NUMBER_TOKEN) {
+                    Some(Value::String(digits)) => digits.parse().ok(),
+                    _ => None,
+                };
+                let number = number.ok_or_else(|| {
+                    EncodeError::Serialize("malformed arbitrary precision number".to_string())
+                })?;
+                self.ser.write_value(Value::Number(number))
+            }
+            Some(map) => self.ser.write_value(Value::Object(map)),
+            None => Ok(()),
+        }
+    }
+}
+
+impl<W: Write> ser::SerializeMap for FieldWriter<'_, '_, W> {
+    type Ok = ();
+    type Error = EncodeError;
+
+    fn serialize_key<T: Serialize + ?Sized>(&mut self, key: &T) -> Result<(), EncodeError> {
+        self.pending_key = Some(map_key(key)?);
+        Ok(())
+    }
+
+    fn serialize_value<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), EncodeError> {
+        let key = self
+            .pending_key
+            .take()
+            .ok_or_else(|| EncodeError::Serialize("map value without a key".to_string()))?;
+        self.write_field(key, value)
+    }
+
+    fn end(self) -> Result<(), EncodeError> {
+        self.finish()
+    }
+}
+
+impl<W: Write> ser::SerializeStruct for FieldWriter<'_, '_, W> {
+    type Ok = ();
+    type Error = EncodeError;
+
+    fn serialize_field<T: Serialize + ?Sized>(
+        &mut self,
+        key: &'static str,
+        value: &T,
+    ) -> Result<(), EncodeError> {
+        self.write_field(key.to_string(), value)
+    }
+
+    fn end(self) -> Result<(), EncodeError> {
+        self.finish()
+    }
+}
+
+/// Walks a value without output, failing on the first non-finite float
+struct FiniteCheck;
+
+impl FiniteCheck {
+    fn float(v: f64) -> Result<(), EncodeError> {
+        if v.is_finite() {
+            Ok(())
+        } else {
+            Err(EncodeError::NonFiniteNumber(v))
+        }
+    }
+}
+
+impl ser::Serializer for FiniteCheck {
+    type Ok = ();
+    type Error = EncodeError;
+
+    type SerializeSeq = Self;
+    type SerializeTuple = Self;
+    type SerializeTupleStruct = Self;
+    type SerializeTupleVariant = Self;
+    type SerializeMap = Self;
+    type SerializeStruct = Self;
+    type SerializeStructVariant = Self;
+
+    fn serialize_bool(self, _v: bool) -> Result<(), EncodeError> {
+        Ok(())
+    }
+
+    fn serialize_i8(self, _v: i8) -> Result<(), EncodeError> {
+        Ok(())
+    }
+
+    fn serialize_i16(self, _v: i16) -> Result<(), EncodeError> {
+        Ok(())
+    }
+
+    fn serialize_i32(self, _v: i32) -> Result<(), EncodeError> {
+        Ok(())
+    }
+
+    fn serialize_i64(self, _v: i64) -> Result<(), EncodeError> {
+        Ok(())
+    }
+
+    fn serialize_u8(self, _v: u8) -> Result<(), EncodeError> {
+        Ok(())
+    }
+
+    fn serialize_u16(self, _v: u16) -> Result<(), EncodeError> {
+        Ok(())
+    }
+
+    fn serialize_u32(self, _v: u32) -> Result<(), EncodeError> {
+        Ok(())
+    }
+
+    fn serialize_u64(self, _v: u64) -> Result<(), EncodeError> {
+        Ok(())
+    }
+
+    fn serialize_f32(self, v: f32) -> Result<(), EncodeError> {
+        Self::float(v.into())
+    }
+
+    fn serialize_f64(self, v: f64) -> Result<(), EncodeError> {
+        Self::float(v)
+    }
+
+    fn serialize_char(self, _v: char) -> Result<(), EncodeError> {
+        Ok(())
+    }
+
+    fn serialize_str(self, _v: &str) -> Result<(), EncodeError> {
+        Ok(())
+    }
+
+    fn serialize_bytes(self, _v: &[u8]) -> Result<(), EncodeError> {
+        Ok(())
+    }
+
+    fn serialize_none(self) -> Result<(), EncodeError> {
+        Ok(())
+    }
+
+    fn serialize_some<T: Serialize + ?Sized>(self, value: &T) -> Result<(), EncodeError> {
+        value.serialize(self)
+    }
+
+    fn serialize_unit(self) -> Result<(), EncodeError> {
+        Ok(())
+    }
+
+    fn serialize_unit_struct(self, _name: &'static str) -> Result<(), EncodeError> {
+        Ok(())
+    }
+
+    fn serialize_unit_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        _variant: &'static str,
+    ) -> Result<(), EncodeError> {
+        Ok(())
+    }
+
+    fn serialize_newtype_struct<T: Serialize + ?Sized>(
+        self,
+        _name: &'static str,
+        value: &T,
+    ) -> Result<(), EncodeError> {
+        value.serialize(self)
+    }
+
+    fn serialize_newtype_variant<T: Serialize + ?Sized>(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        _variant: &'static str,
+        value: &T,
+    ) -> Result<(), EncodeError> {
+        value.serialize(self)
+    }
+
+    fn serialize_seq(self, _len: Option<usize>) -> Result<Self, EncodeError> {
+        Ok(self)
+    }
+
+    fn serialize_tuple(self, _len: usize) -> Result<Self, EncodeError> {
+        Ok(self)
+    }
+
+    fn serialize_tuple_struct(self, _name: &'static str, _len: usize) -> Result<Self, EncodeError> {
+        Ok(self)
+    }
+
+    fn serialize_tuple_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        _variant: &'static str,
+        _len: usize,
+    ) -> Result<Self, EncodeError> {
+        Ok(self)
+    }
+
+    fn serialize_map(self, _len: Option<usize>) -> Result<Self, EncodeError> {
+        Ok(self)
+    }
+
+    fn serialize_struct(self, _name: &'static str, _len: usize) -> Result<Self, EncodeError> {
+        Ok(self)
+    }
+
+    fn serialize_struct_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        _variant: &'static str,
+        _len: usize,
+    ) -> Result<Self, EncodeError> {
+        Ok(self)
+    }
+}
+
+impl ser::SerializeSeq for FiniteCheck {
+    type Ok = ();
+    type Error = EncodeError;
+
+    fn serialize_element<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), EncodeError> {
+        value.serialize(FiniteCheck)
+    }
+
+    fn end(self) -> Result<(), EncodeError> {
+        Ok(())
+    }
+}
+
+impl ser::SerializeTuple for FiniteCheck {
+    type Ok = ();
+    type Error = EncodeError;
+
+    fn serialize_element<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), EncodeError> {
+        value.serialize(FiniteCheck)
+    }
+
+    fn end(self) -> Result<(), EncodeError> {
+        Ok(())
+    }
+}
+
+impl ser::SerializeTupleStruct for FiniteCheck {
+    type Ok = ();
+    type Error = EncodeError;
+
+    fn serialize_field<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), EncodeError> {
+        value.serialize(FiniteCheck)
+    }
+
+    fn end(self) -> Result<(), EncodeError> {
+        Ok(())
+    }
+}
+
+impl ser::SerializeTupleVariant for FiniteCheck {
+    type Ok = ();
+    type Error = EncodeError;
+
+    fn serialize_field<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), EncodeError> {
+        value.serialize(FiniteCheck)
+    }
+
+    fn end(self) -> Result<(), EncodeError> {
+        Ok(())
+    }
+}
+
+impl ser::SerializeMap for FiniteCheck {
+    type Ok = ();
+    type Error = EncodeError;
+
+    fn serialize_key<T: Serialize + ?Sized>(&mut self, key: &T) -> Result<(), EncodeError> {
+        key.serialize(FiniteCheck)
+    }
+
+    fn serialize_value<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), EncodeError> {
+        value.serialize(FiniteCheck)
+    }
+
+    fn end(self) -> Result<(), EncodeError> {
+        Ok(())
+    }
+}
+
+impl ser::SerializeStruct for FiniteCheck {
+    type Ok = ();
+    type Error = EncodeError;
+
+    fn serialize_field<T: Serialize + ?Sized>(
+        &mut self,
+        _key: &'static str,
+        value: &T,
+    ) -> Result<(), EncodeError> {
+        value.serialize(FiniteCheck)
+    }
+
+    fn end(self) -> Result<(), EncodeError> {
+        Ok(())
+    }
+}
+
+impl ser::SerializeStructVariant for FiniteCheck {
+    type Ok = ();
+    type Error = EncodeError;
+
+    fn serialize_field<T: Serialize + ?Sized>(
+        &mut self,
+        _key: &'static str,
+        value: &T,
+    ) -> Result<(), EncodeError> {
+        value.serialize(FiniteCheck)
+    }
+
+    fn end(self) -> Result<(), EncodeError> {
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::encoder::encode;
+    use serde::Serialize;
+    use serde_json::json;
+    use std::collections::BTreeMap;
+
+    #[derive(Serialize)]
+    struct Row {
+        id: u32,
+        name: String,
+        score: f64,
+    }
+
+    #[derive(Serialize)]
+    enum Shape {
+        Point,
+        Circle(f64),
+        Rect { w: u32, h: u32 },
+        Pair(i32, i32),
+    }
+
+    #[derive(Serialize)]
+    struct Doc {
+        title: String,
+        meta: BTreeMap<String, Option<u8>>,
+        empty: BTreeMap<String, u8>,
+        rows: Vec<Row>,
+        tags: Vec<&'static str>,
+        shapes: Vec<Shape>,
+        shape: Shape,
+        nested: Option<Box<Doc>>,
+    }
+
+    fn sample_doc() -> Doc {
+        Doc {
+            title: "Report: Q1".to_string(),
+            meta: BTreeMap::from([("a".to_string(), Some(1)), ("b".to_string(), None)]),
+            empty: BTreeMap::new(),
+            rows: vec![],
+            tags: vec!["x", "y"],
+            shapes: vec![
+                Shape::Point,
+                Shape::Circle(1.5),
+                Shape::Rect { w: 2, h: 3 },
+                Shape::Pair(-1, 1),
+            ],
+            shape: Shape::Rect { w: 1, h: 1 },
+            nested: Some(Box::new(Doc {
+                title: "inner".to_string(),
+                meta: BTreeMap::new(),
+                empty: BTreeMap::new(),
+                rows: vec![Row {
+                    id: 7,
+                    name: "Z".to_string(),
+                    score: 0.5,
+                }],
+                tags: vec![],
+                shapes: vec![],
+                shape: Shape::Point,
+                nested: None,
+            })),
+        }
+    }
+
+    #[test]
+    fn test_to_string_tabular_rows() {
+        let rows = vec![
+            Row {
+                id: 1,
+                name: "Alice".to_string(),
+                score: 9.5,
+            },
+            Row {
+                id: 2,
+                name: "Bob".to_string(),
+                score: 7.0,
+            },
+        ];
+        let toon = to_string(&rows, &EncoderOptions::default()).unwrap();
+        assert_eq!(toon, "[2]{id,name,score}:\n  1,Alice,9.5\n  2,Bob,7");
+    }
+
+    #[test]
+    fn test_to_string_matches_encode() {
+        let doc = sample_doc();
+        let value = serde_json::to_value(&doc).unwrap();
+
+        for options in [
+            EncoderOptions::default(),
+            EncoderOptions {
+                sort_keys: true,
+                trailing_newline: true,
+                ..Default::default()
+            },
+            EncoderOptions {
+                skip_nulls: true,
+                ..Default::default()
+            },
+        ] {
+            assert_eq!(to_string(&doc, &options).unwrap(), encode(&value, &options));
+        }
+
+        for primitive in [json!(42), json!("42"), json!(null), json!(true)] {
+            let options = EncoderOptions::default();
+            assert_eq!(
+                to_string(&primitive, &options).unwrap(),
+                encode(&primitive, &options)
+            );
+        }
+    }
+
+    #[test]
+    fn test_to_string_rejects_non_string_keys() {
+        let map = BTreeMap::from([(vec![1u8], 1)]);
+        assert!(matches!(
+            to_string(&map, &EncoderOptions::default()),
+            Err(EncodeError::Serialize(_))
+        ));
+
+        let map = BTreeMap::from([(1, "a"), (2, "b")]);
+        assert_eq!(
+            to_string(&map, &EncoderOptions::default()).unwrap(),
+            "\"1\": a\n\"2\": b"
+        );
+    }
+
+    #[test]
+    fn test_try_encode_rejects_non_finite_numbers() {
+        let options = EncoderOptions::default();
+        let rows = vec![
+            Row {
+                id: 1,
+                name: "a".to_string(),
+                score: 0.5,
+            },
+            Row {
+                id: 2,
+                name: "b".to_string(),
+                score: f64::NAN,
+            },
+        ];
+        let err = try_encode(&rows, &options).unwrap_err();
+        assert!(matches!(err, EncodeError::NonFiniteNumber(v) if v.is_nan()));
+        assert_eq!(
+            err.to_string(),
+            "Non-finite number NaN cannot be represented in TOON"
+        );
+        // The lossy paths write `null` instead
+        assert!(to_string(&rows, &options).unwrap().ends_with("2,b,null"));
+
+        let nested = BTreeMap::from([("limits", vec![Some(1.0f32), Some(f32::INFINITY)])]);
+        assert!(matches!(
+            try_encode(&nested, &options),
+            Err(EncodeError::NonFiniteNumber(v)) if v == f64::INFINITY
+        ));
+        assert!(matches!(
+            try_encode(&Shape::Circle(f64::NEG_INFINITY), &options),
+            Err(EncodeError::NonFiniteNumber(_))
+        ));
+
+        assert_eq!(
+            try_encode(&rows[0], &options).unwrap(),
+            to_string(&rows[0], &options).unwrap()
+        );
+    }
+
+    #[cfg(feature = "arbitrary_precision")]
+    #[test]
+    fn test_to_string_arbitrary_precision_numbers() {
+        let value: Value = serde_json::from_str(
+            r#"{"big": 123456789012345678901234567890, "rows": [{"n": -98765432109876543210}, {"n": 1.5}], "list": [42, 1e400]}"#,
+        )
+        .unwrap();
+        for options in [
+            EncoderOptions::default(),
+            EncoderOptions {
+                sort_keys: true,
+                ..Default::default()
+            },
+        ] {
+            assert_eq!(
+                to_string(&value, &options).unwrap(),
+                encode(&value, &options)
+            );
+        }
+        assert_eq!(
+            to_string(&value["big"], &EncoderOptions::default()).unwrap(),
+            "123456789012345678901234567890"
+        );
+    }
+}
@@ -1,10 +1,14 @@
-use json2toon_rs::{decode, DecoderOptions};
+use json2toon_rs::{
+    decode, decode_with_span, detect_indent, validate, DecodeError, DecoderOptions, ErrorSpan,
+    IndentStyle,
+};
 use serde_json::json;
 
 fn default_opts() -> DecoderOptions {
     DecoderOptions {
-        indent: 2,
+        indent: IndentStyle::Spaces(2),
         strict: true,
+        ..Default::default()
     }
 }
 
@@ -20,6 +24,25 @@ fn decode_single_primitive_root() {
     assert_eq!(json, json!("hello"));
 }
 
+#[test]
+fn decode_root_array_rejects_trailing_lines() {
+    assert_eq!(
+        decode("[2]: 1,2\nx: 1", &default_opts()),
+        Err(DecodeError::InvalidLine {
+            line: 2,
+            content: "x: 1".to_string()
+        })
+    );
+    assert_eq!(
+        decode("[1]:\n  - a: 1\n    b: 2", &default_opts()),
+        Err(DecodeError::InvalidIndentation { line: 3 })
+    );
+    assert_eq!(
+        decode("[2]: 1,2\nx: 1", &DecoderOptions::lenient()).unwrap(),
+        json!([1, 2])
+    );
+}
+
 #[test]
 fn decode_simple_object() {
     let input = "name: Alice\nage: 30";
@@ -81,7 +104,12 @@ fn decode_array_length_mismatch_inline() {
     let input = "tags[2]: one,two,three";
     let err = decode(input, &default_opts()).unwrap_err();
     match err {
-        json2toon_rs::DecodeError::ArrayLengthMismatch { expected, found } => {
+        json2toon_rs::DecodeError::ArrayLengthMismatch {
+            line,
+            expected,
+            found,
+        } => {
+            assert_eq!(line, 1);
             assert_eq!(expected, 2);
             assert_eq!(found, 3);
         }
@@ -112,10 +140,1200 @@ fn decode_array_length_mismatch_tabular_rows() {
     let input = "users[3]{id,name}:\n  1,Alice\n  2,Bob"; // only 2 rows instead of 3
     let err = decode(input, &default_opts()).unwrap_err();
     match err {
-        json2toon_rs::DecodeError::ArrayLengthMismatch { expected, found } => {
+        json2toon_rs::DecodeError::ArrayLengthMismatch {
+            line,
+            expected,
+            found,
+        } => {
+            assert_eq!(line, 1);
             assert_eq!(expected, 3);
             assert_eq!(found, 2);
         }
         _ => panic!("expected ArrayLengthMismatch error"),
     }
 }
+
+#[test]
+fn decode_errors_report_line() {
+    let input = "name: Alice\nusers[3]{id,name}:\n  1,Alice\n  2,Bob";
+    let err = decode(input, &default_opts()).unwrap_err();
+    assert_eq!(err.line(), Some(2));
+    assert_eq!(
+        err.to_string(),
+        "Array length mismatch at line 2: expected 3, got 2"
+    );
+
+    let input = "ok: 1\nbad[x]: 1,2";
+    let err = decode(input, &default_opts()).unwrap_err();
+    assert_eq!(
+        err,
+        json2toon_rs::DecodeError::InvalidArrayHeader {
+            line: 2,
+            message: "Invalid array length".to_string()
+        }
+    );
+}
+
+#[test]
+fn decode_with_comments() {
+    let plain =
+        "server:\n  host: localhost\n  port: 8080\ntags[2]: \"#a\",b#\nname: \"# not a comment\"";
+    let commented = "# Server configuration\nserver:\n  # bind address\n  host: localhost\n    # odd indentation is fine for comments\n  port: 8080\ntags[2]: \"#a\",b#\n# trailing\nname: \"# not a comment\"";
+
+    let opts = DecoderOptions {
+        allow_comments: true,
+        ..default_opts()
+    };
+    let expected = decode(plain, &default_opts()).unwrap();
+    assert_eq!(decode(commented, &opts).unwrap(), expected);
+    assert_eq!(
+        expected,
+        json!({
+            "server": {"host": "localhost", "port": 8080},
+            "tags": ["#a", "b#"],
+            "name": "# not a comment"
+        })
+    );
+}
+
+#[test]
+fn decode_comments_round_trip_hash_cells() {
+    let opts = DecoderOptions {
+        allow_comments: true,
+        ..default_opts()
+    };
+    let data = json!({"rows": [{"id": "#1", "n": 1}, {"id": "#2", "n": 2}], "tag": "#x"});
+    let toon = json2toon_rs::encode(&data, &json2toon_rs::EncoderOptions::default());
+    assert_eq!(toon, "rows[2]{id,n}:\n  \"#1\",1\n  \"#2\",2\ntag: \"#x\"");
+    assert_eq!(decode(&toon, &opts).unwrap(), data);
+    assert_eq!(decode("\"#x\"", &opts).unwrap(), json!("#x"));
+}
+
+#[test]
+fn decode_comments_rejected_by_default() {
+    let input = "# comment\nname: Alice";
+    assert!(decode(input, &default_opts()).is_err());
+}
+
+#[test]
+fn decode_presets() {
+    assert!(DecoderOptions::strict().strict);
+    assert!(!DecoderOptions::lenient().strict);
+
+    let input = "meta:\n   version: 1\nusers[3]{id,name,role}:\n   1,Alice,admin\n   2,Bob";
+    assert!(decode(input, &DecoderOptions::strict()).is_err());
+
+    let value = decode(input, &DecoderOptions::lenient()).unwrap();
+    assert_eq!(value["meta"], json!({"version": 1}));
+    assert_eq!(
+        value["users"],
+        json!([
+            {"id": 1, "name": "Alice", "role": "admin"},
+            {"id": 2, "name": "Bob", "role": null}
+        ])
+    );
+}
+
+#[test]
+fn validate_valid_input() {
+    let input = "users[2]{id,name}:\n  1,Alice\n  2,Bob\ntags[2]: a,b";
+    assert!(validate(input, &default_opts()).is_empty());
+}
+
+#[test]
+fn validate_collects_all_errors() {
+    let input = "users[3]{id,name}:\n  1,Alice,admin\n  2\n  3,Carol\nname: x\nname: y";
+    let errors = validate(input, &default_opts());
+    assert_eq!(
+        errors,
+        vec![
+            DecodeError::RowWidthMismatch {
+                line: 2,
+                expected: 2,
+                found: 3
+            },
+            DecodeError::RowWidthMismatch {
+                line: 3,
+                expected: 2,
+                found: 1
+            },
+            DecodeError::DuplicateKey {
+                line: 6,
+                key: "name".to_string()
+            },
+        ]
+    );
+}
+
+#[test]
+fn validate_reports_fatal_error_last() {
+    let input = "a:\n   b: \"\\q\"\nc[x]: 1";
+    let errors = validate(input, &default_opts());
+    assert_eq!(errors.len(), 3);
+    assert!(matches!(
+        errors[0],
+        DecodeError::InvalidIndentation { line: 2 }
+    ));
+    assert!(matches!(
+        errors[1],
+        DecodeError::InvalidEscapeSequence { line: 2, .. }
+    ));
+    assert!(matches!(
+        errors[2],
+        DecodeError::InvalidArrayHeader { line: 3, .. }
+    ));
+}
+
+#[test]
+fn toon_newtype_parse() {
+    let toon: json2toon_rs::Toon = "tags[2]: a,b".parse().unwrap();
+    assert_eq!(toon.0, json!({"tags": ["a", "b"]}));
+    assert_eq!(toon.to_string(), "tags[2]: a,b");
+
+    let err = "tags[3]: a,b".parse::<json2toon_rs::Toon>().unwrap_err();
+    assert!(matches!(err, DecodeError::ArrayLengthMismatch { .. }));
+}
+
+#[test]
+fn decode_error_spans() {
+    let span_text = |input: &str| {
+        let (_, span) = decode_with_span(input, &default_opts()).unwrap_err();
+        let ErrorSpan { start, end } = span.unwrap();
+        input[start..end].to_string()
+    };
+
+    assert_eq!(span_text("a: 1\nb:\n  c: \"x\\\\n\\qy\""), "\\q");
+    assert_eq!(span_text("name: x\n\n\"name\": y"), "\"name\"");
+    assert_eq!(span_text("ok: 1\nbad[x]: 1,2"), "[x]");
+    assert_eq!(span_text("users[2]{id,name}:\n  1,Alice\n  2"), "2");
+    assert_eq!(span_text("key:\n   bad_indent: 1"), "bad_indent: 1");
+
+    let input = "tags[2]: a,b";
+    assert_eq!(
+        decode_with_span(input, &default_opts()),
+        decode(input, &default_opts()).map_err(|e| (e, None))
+    );
+}
+
+#[test]
+fn decode_lenient_infers_indent() {
+    let input = "server:\n    host: localhost\n    tls:\n        enabled: true\nports[2]:\n    - 80\n    - 443";
+    let expected = json!({
+        "server": {"host": "localhost", "tls": {"enabled": true}},
+        "ports": [80, 443]
+    });
+
+    assert_eq!(decode(input, &DecoderOptions::lenient()).unwrap(), expected);
+    assert!(decode(input, &DecoderOptions::strict()).is_err());
+
+    let mut streaming = json2toon_rs::StreamingDecoder::new(DecoderOptions::lenient());
+    streaming.push_str(input);
+    assert_eq!(streaming.finish().unwrap(), expected);
+}
+
+#[test]
+fn decode_crlf_line_endings() {
+    let lf = "server:\n  host: \"a b \"\n  tags[2]: x,y\nusers[2]{id,name}:\n  1,Alice\n  2,Bob\n";
+    let expected = decode(lf, &default_opts()).unwrap();
+
+    let crlf = lf.replace('\n', "\r\n");
+    assert_eq!(decode(&crlf, &default_opts()).unwrap(), expected);
+
+    let cr = lf.replace('\n', "\r");
+    assert_eq!(decode(&cr, &default_opts()).unwrap(), expected);
+
+    // Chunks split between `\r` and `\n` must not produce an extra line
+    let mut streaming = json2toon_rs::StreamingDecoder::new(default_opts());
+    for chunk in crlf.split_inclusive('\r') {
+        streaming.push_str(chunk);
+    }
+    assert_eq!(streaming.finish().unwrap(), expected);
+
+    let (err, span) = decode_with_span("a: 1\r\nb: \"\\q\"\r\n", &default_opts()).unwrap_err();
+    assert_eq!(err.line(), Some(2));
+    assert_eq!(span, Some(ErrorSpan { start: 10, end: 12 }));
+}
+
+#[test]
+fn decode_ragged_rows() {
+    let opts = DecoderOptions {
+        ragged_rows: true,
+        ..default_opts()
+    };
+    assert!(DecoderOptions::lenient().ragged_rows);
+
+    let input = "users[2]{id,name}:\n  1\n  2,Bob";
+    assert!(decode(input, &default_opts()).is_err());
+    assert_eq!(
+        decode(input, &opts).unwrap(),
+        json!({"users": [{"id": 1, "name": null}, {"id": 2, "name": "Bob"}]})
+    );
+
+    // Wide rows would lose data, so strict mode still rejects them
+    let input = "users[1]{id,name}:\n  1,Alice,admin";
+    assert_eq!(
+        decode(input, &opts).unwrap_err(),
+        DecodeError::RowWidthMismatch {
+            line: 2,
+            expected: 2,
+            found: 3
+        }
+    );
+}
+
+#[test]
+fn decode_malformed_input_does_not_panic() {
+    // Inputs that previously panicked on byte-index slicing
+    let inputs = [
+        "[€]:",
+        "\"\\",
+        "\"",
+        "x: \"",
+        "a[1]: \"",
+        "[1]:\n  - \"",
+        "x[1]{a}:\n  \"",
+        "a[1|]{x|\"}:",
+        "[€",
+    ];
+    for input in inputs {
+        let _ = decode(input, &default_opts());
+        let _ = decode(input, &DecoderOptions::lenient());
+        let _ = validate(input, &default_opts());
+    }
+
+    for input in ["\"", "x: \"", "[1]:\n  - \"", "x: \"abc"] {
+        assert!(matches!(
+            decode(input, &default_opts()),
+            Err(DecodeError::ParseError { line: 1 | 2, .. })
+        ));
+    }
+    assert_eq!(
+        decode("x: \"", &DecoderOptions::lenient()).unwrap(),
+        json!({"x": "\""})
+    );
+    assert_eq!(
+        decode("é: 1\nké€y: \"v\"", &default_opts()).unwrap(),
+        json!({"é": 1, "ké€y": "v"})
+    );
+}
+
+#[test]
+fn decode_omitted_lengths() {
+    let input =
+        "tags[]: a,b,c\nusers[]{id,name}:\n  1,Alice\n  2,Bob\nitems[]:\n  - 1\n  - [|]: x|y";
+    assert_eq!(
+        decode(input, &default_opts()).unwrap(),
+        json!({
+            "tags": ["a", "b", "c"],
+            "users": [{"id": 1, "name": "Alice"}, {"id": 2, "name": "Bob"}],
+            "items": [1, ["x", "y"]]
+        })
+    );
+}
+
+#[cfg(feature = "std")]
+#[test]
+fn decode_from_reader() {
+    let input = "name: Alice\r\nusers[2]{id,name}:\n  1,Alice\n  2,Bob";
+    let mut reader = std::io::Cursor::new(input.as_bytes().to_vec());
+    assert_eq!(
+        json2toon_rs::decode_reader(&mut reader, &default_opts()).unwrap(),
+        decode(input, &default_opts()).unwrap()
+    );
+
+    let mut reader = std::io::Cursor::new(b"name: \xff".to_vec());
+    let err = json2toon_rs::decode_reader(&mut reader, &default_opts()).unwrap_err();
+    assert!(matches!(err, DecodeError::Io(_)));
+    assert_eq!(err.line(), None);
+}
+
+#[test]
+fn decode_many_documents() {
+    let input =
+        "---\nid: 1\nname: Alice\n---\nid: 2\nname: Bob\n\n---\r\nid: 3\ntags[2]: a,b\n---\n";
+    let docs = json2toon_rs::decode_many(input, &default_opts()).unwrap();
+    assert_eq!(
+        docs,
+        vec![
+            json!({"id": 1, "name": "Alice"}),
+            json!({"id": 2, "name": "Bob"}),
+            json!({"id": 3, "tags": ["a", "b"]}),
+        ]
+    );
+
+    // `---` inside a value or indented is not a separator; errors keep absolute lines
+    let input = "a: ---\n---\nb:\n  c: 1\n---\nd[3]: x,y";
+    let err = json2toon_rs::decode_many(input, &default_opts()).unwrap_err();
+    assert_eq!(
+        err,
+        DecodeError::ArrayLengthMismatch {
+            line: 6,
+            expected: 3,
+            found: 2
+        }
+    );
+}
+
+#[test]
+fn decode_empty_tabular_cells() {
+    let input = "users[1]{id,note,active}:\n  1,,true";
+    assert_eq!(
+        decode(input, &default_opts()).unwrap(),
+        json!({"users": [{"id": 1, "note": "", "active": true}]})
+    );
+
+    let opts = DecoderOptions {
+        empty_cell_as_null: true,
+        ..default_opts()
+    };
+    assert_eq!(
+        decode(input, &opts).unwrap(),
+        json!({"users": [{"id": 1, "note": null, "active": true}]})
+    );
+
+    // A quoted empty string is explicit and stays a string
+    let input = "users[1]{id,note,active}:\n  1,\"\",true";
+    assert_eq!(
+        decode(input, &opts).unwrap(),
+        json!({"users": [{"id": 1, "note": "", "active": true}]})
+    );
+}
+
+#[test]
+fn decode_strips_leading_bom() {
+    let input = "\u{FEFF}name: Alice\nage: 30";
+    let expected = json!({"name": "Alice", "age": 30});
+    assert_eq!(decode(input, &default_opts()).unwrap(), expected);
+
+    let mut streaming = json2toon_rs::StreamingDecoder::new(default_opts());
+    streaming.push_str(input);
+    assert_eq!(streaming.finish().unwrap(), expected);
+
+    // Spans still index the original input
+    let input = "\u{FEFF}a: \"\\q\"";
+    let (_, span) = decode_with_span(input, &default_opts()).unwrap_err();
+    let ErrorSpan { start, end } = span.unwrap();
+    assert_eq!(&input[start..end], "\\q");
+}
+
+#[test]
+fn decode_per_array_delimiters() {
+    assert_eq!(
+        decode("a[2,]: x,y\nb[2|]: p|q", &default_opts()).unwrap(),
+        json!({"a": ["x", "y"], "b": ["p", "q"]})
+    );
+
+    // Nested headers use their own delimiter, not the enclosing array's
+    let input = "rows[2|]{id|tags}:\n  1|a,b\n  2|c\nitems[2]:\n  - [2;]: 1;2\n  - [2\t]: x,y\tz";
+    assert_eq!(
+        decode(input, &default_opts()).unwrap(),
+        json!({
+            "rows": [{"id": 1, "tags": "a,b"}, {"id": 2, "tags": "c"}],
+            "items": [[1, 2], ["x,y", "z"]]
+        })
+    );
+}
+
+#[test]
+fn canonicalize_is_idempotent() {
+    let options = json2toon_rs::EncoderOptions {
+        sort_keys: true,
+        ..Default::default()
+    };
+    let canonical =
+        |input: &str| json2toon_rs::canonicalize(input, &default_opts(), &options).unwrap();
+
+    let input = "zeta:\n  count: 1.50\n  ratio: 1e3\nusers[2|]{name|id}:\n  Bob|2\n  \"Alice\"|1\n\
+                 items[3]:\n  - [2]: a,\"b\"\n  - k: v\n  j: -0.0\n  - text\nalpha: \"x\"";
+    let once = canonical(input);
+    assert_eq!(canonical(&once), once);
+    assert_eq!(
+        once,
+        "alpha: x\nitems[3]:\n  - [2]: a,b\n  - j: 0\n  k: v\n  - text\n\
+         users[2]{id,name}:\n  2,Bob\n  1,Alice\nzeta:\n  count: 1.5\n  ratio: 1000"
+    );
+}
+
+#[test]
+fn decode_array_shaped_tabular_cells() {
+    // Quoted, the cell is an ordinary string
+    let input = "users[1]{id,scores}:\n  1,\"[2]: 10,20\"";
+    assert_eq!(
+        decode(input, &default_opts()).unwrap(),
+        json!({"users": [{"id": 1, "scores": "[2]: 10,20"}]})
+    );
+
+    // Unquoted, it is rejected rather than split into extra cells
+    let input = "users[1]{id,scores}:\n  1,[2]: 10,20";
+    let err = decode(input, &default_opts()).unwrap_err();
+    assert!(matches!(err, DecodeError::ParseError { line: 2, .. }));
+    assert!(err.to_string().contains("Tabular cells cannot hold arrays"));
+
+    let input = "users[1|]{id|scores}:\n  1|[2]: 10,20";
+    assert!(matches!(
+        decode(input, &default_opts()),
+        Err(DecodeError::ParseError { line: 2, .. })
+    ));
+}
+
+#[test]
+fn decode_tabular_field_types() {
+    use json2toon_rs::ValueType;
+
+    let input = "rows[2]{zip,count,active}:\n  02139,\"7\",\"true\"\n  94107,12,null";
+    assert_eq!(
+        decode(input, &default_opts()).unwrap(),
+        json!({"rows": [
+            {"zip": "02139", "count": "7", "active": "true"},
+            {"zip": 94107, "count": 12, "active": null}
+        ]})
+    );
+
+    let opts = DecoderOptions {
+        field_types: Some(
+            [
+                ("zip".to_string(), ValueType::String),
+                ("count".to_string(), ValueType::Number),
+                ("active".to_string(), ValueType::Bool),
+            ]
+            .into_iter()
+            .collect(),
+        ),
+        ..default_opts()
+    };
+    assert_eq!(
+        decode(input, &opts).unwrap(),
+        json!({"rows": [
+            {"zip": "02139", "count": 7, "active": true},
+            {"zip": "94107", "count": 12, "active": null}
+        ]})
+    );
+
+    // Cells that cannot be coerced are errors in strict mode only
+    let input = "rows[1]{zip,count,active}:\n  1,many,yes";
+    assert!(matches!(
+        decode(input, &opts),
+        Err(DecodeError::ParseError { line: 2, .. })
+    ));
+    let lenient = DecoderOptions {
+        strict: false,
+        ..opts
+    };
+    assert_eq!(
+        decode(input, &lenient).unwrap(),
+        json!({"rows": [{"zip": "1", "count": "many", "active": "yes"}]})
+    );
+}
+
+#[test]
+fn decode_rejects_raw_control_characters() {
+    let input = "tags[2]: a\tb,c";
+    assert_eq!(
+        decode(input, &default_opts()).unwrap_err(),
+        DecodeError::InvalidCharacter { line: 1, ch: '\t' }
+    );
+    let (_, span) = decode_with_span(input, &default_opts()).unwrap_err();
+    assert_eq!(span, Some(ErrorSpan { start: 10, end: 11 }));
+
+    assert!(matches!(
+        decode("name: Al\u{7}ice", &default_opts()),
+        Err(DecodeError::InvalidCharacter {
+            line: 1,
+            ch: '\u{7}'
+        })
+    ));
+
+    // Quoted and escaped, or with a tab delimiter, the tab is fine
+    assert_eq!(
+        decode("tags[2]: \"a\tb\",c", &default_opts()).unwrap(),
+        json!({"tags": ["a\tb", "c"]})
+    );
+    assert_eq!(
+        decode("tags[2\t]: a\tb", &default_opts()).unwrap(),
+        json!({"tags": ["a", "b"]})
+    );
+
+    // Lenient mode keeps the character
+    assert_eq!(
+        decode(input, &DecoderOptions::lenient()).unwrap(),
+        json!({"tags": ["a\tb", "c"]})
+    );
+}
+
+#[test]
+fn decode_custom_null_tokens() {
+    let input = "a: ~\nb: nil\nc: null\nd: \"null\"\ne: \"~\"\nv[3]: ~,nil,x";
+    assert_eq!(
+        decode(input, &default_opts()).unwrap(),
+        json!({"a": "~", "b": "nil", "c": null, "d": "null", "e": "~", "v": ["~", "nil", "x"]})
+    );
+
+    let opts = DecoderOptions {
+        null_tokens: vec!["null".to_string(), "~".to_string(), "nil".to_string()],
+        ..default_opts()
+    };
+    assert_eq!(
+        decode(input, &opts).unwrap(),
+        json!({"a": null, "b": null, "c": null, "d": "null", "e": "~", "v": [null, null, "x"]})
+    );
+}
+
+#[test]
+fn decode_rejects_dangling_over_indented_lines() {
+    let input = "a: 1\n  b: 2\nc: 3";
+    assert_eq!(
+        decode(input, &default_opts()).unwrap_err(),
+        DecodeError::InvalidIndentation { line: 2 }
+    );
+    assert_eq!(
+        validate(input, &default_opts()),
+        vec![DecodeError::InvalidIndentation { line: 2 }]
+    );
+
+    let input = "rows[1]{id}:\n  1\n    2\nnext: x";
+    assert_eq!(
+        decode(input, &default_opts()).unwrap_err(),
+        DecodeError::InvalidIndentation { line: 3 }
+    );
+
+    // Lenient mode still skips the line
+    assert_eq!(
+        decode("a: 1\n  b: 2\nc: 3", &DecoderOptions::lenient()).unwrap(),
+        json!({"a": 1, "c": 3})
+    );
+}
+
+#[test]
+fn round_trip_preserves_key_order() {
+    let data = json!({"z": 1, "a": 2, "m": {"y": true, "b": false}});
+    let toon = json2toon_rs::encode(&data, &json2toon_rs::EncoderOptions::default());
+    assert_eq!(toon, "z: 1\na: 2\nm:\n  y: true\n  b: false");
+
+    let decoded = decode(&toon, &default_opts()).unwrap();
+    let keys: Vec<&str> = decoded
+        .as_object()
+        .unwrap()
+        .keys()
+        .map(String::as_str)
+        .collect();
+    assert_eq!(keys, ["z", "a", "m"]);
+    let nested: Vec<&str> = decoded["m"]
+        .as_object()
+        .unwrap()
+        .keys()
+        .map(String::as_str)
+        .collect();
+    assert_eq!(nested, ["y", "b"]);
+}
+
+#[test]
+fn decode_with_warnings_reports_tolerated_mismatches() {
+    use json2toon_rs::{decode_with_warnings, Warning};
+
+    let input = "users[5]{id,name}:\n  1,Alice\n  2,Bob";
+    let (value, warnings) = decode_with_warnings(input, &DecoderOptions::lenient()).unwrap();
+    assert_eq!(value["users"].as_array().unwrap().len(), 2);
+    assert_eq!(
+        warnings,
+        vec![Warning::ArrayLengthMismatch {
+            line: 1,
+            expected: 5,
+            found: 2
+        }]
+    );
+    assert_eq!(
+        warnings[0].to_string(),
+        "Array length mismatch at line 1: expected 5, got 2"
+    );
+
+    // Strict mode fails instead, and valid input has no warnings
+    assert!(decode_with_warnings(input, &default_opts()).is_err());
+    let (_, warnings) = decode_with_warnings("tags[2]: a,b", &default_opts()).unwrap();
+    assert!(warnings.is_empty());
+
+    // Short rows accepted through ragged_rows are reported even when strict
+    let opts = DecoderOptions {
+        ragged_rows: true,
+        ..default_opts()
+    };
+    let (_, warnings) = decode_with_warnings("users[2]{id,name}:\n  1\n  2,Bob", &opts).unwrap();
+    assert_eq!(
+        warnings,
+        vec![Warning::RowWidthMismatch {
+            line: 2,
+            expected: 2,
+            found: 1
+        }]
+    );
+}
+
+#[test]
+fn decode_quoted_keys_with_colons_and_brackets() {
+    let data = json!({
+        "a:b": 1,
+        "x[0]": 2,
+        "true": 3,
+        "y[1]": [4],
+        "nested": {"x[0]": {"a:b": "c"}}
+    });
+    let toon = json2toon_rs::encode(&data, &json2toon_rs::EncoderOptions::default());
+    assert_eq!(
+        toon,
+        "\"a:b\": 1\n\"x[0]\": 2\ntrue: 3\n\"y[1]\"[1]: 4\nnested:\n  \"x[0]\":\n    \"a:b\": c"
+    );
+    assert_eq!(decode(&toon, &default_opts()).unwrap(), data);
+
+    // Brackets after an unquoted key still open an array header
+    let result = decode("x[1]: 5", &default_opts()).unwrap();
+    assert_eq!(result, json!({"x": [5]}));
+}
+
+#[test]
+fn semantically_equal_ignores_formatting() {
+    use json2toon_rs::{encode, semantically_equal, Delimiter, EncoderOptions, Side};
+
+    let data = json!({
+        "tags": ["a", "b|c", "d,e"],
+        "users": [{"id": 1, "name": "Alice"}, {"id": 2, "name": "Bob"}]
+    });
+    let comma = encode(&data, &EncoderOptions::default());
+    let pipe = encode(
+        &data,
+        &EncoderOptions {
+            delimiter: Delimiter::Pipe,
+            ..Default::default()
+        },
+    );
+    assert_ne!(comma, pipe);
+    assert_eq!(semantically_equal(&comma, &pipe, &default_opts()), Ok(true));
+
+    let other = comma.replace("Bob", "Carol");
+    assert_eq!(
+        semantically_equal(&comma, &other, &default_opts()),
+        Ok(false)
+    );
+
+    let (side, err) = semantically_equal("tags[3]: a", &pipe, &default_opts()).unwrap_err();
+    assert_eq!(side, Side::A);
+    assert!(matches!(err, DecodeError::ArrayLengthMismatch { .. }));
+    let (side, _) = semantically_equal(&comma, "tags[3]: a", &default_opts()).unwrap_err();
+    assert_eq!(side, Side::B);
+}
+
+#[test]
+fn decode_checked_reports_all_mismatches() {
+    use json2toon_rs::{decode_checked, ErrorKind};
+
+    let input = "users[3]{id,name}:\n  1,Alice\n  2\ntags[1]: a,b\nok[2]: x,y";
+    let err = decode_checked(input, &default_opts()).unwrap_err();
+    let DecodeError::Multiple(errors) = &err else {
+        panic!("expected several errors, got {:?}", err);
+    };
+    assert_eq!(
+        errors,
+        &vec![
+            DecodeError::ArrayLengthMismatch {
+                line: 1,
+                expected: 3,
+                found: 2
+            },
+            DecodeError::RowWidthMismatch {
+                line: 3,
+                expected: 2,
+                found: 1
+            },
+            DecodeError::ArrayLengthMismatch {
+                line: 4,
+                expected: 1,
+                found: 2
+            },
+        ]
+    );
+    assert_eq!(err.kind(), ErrorKind::Multiple);
+    assert_eq!(err.line(), Some(1));
+    assert!(err
+        .to_string()
+        .starts_with("3 errors\n  Array length mismatch at line 1"));
+
+    // A single error is returned unwrapped, and valid input decodes normally
+    let err = decode_checked("tags[1]: a,b", &default_opts()).unwrap_err();
+    assert!(matches!(
+        err,
+        DecodeError::ArrayLengthMismatch { line: 1, .. }
+    ));
+    let value = decode_checked("tags[2]: a,b", &default_opts()).unwrap();
+    assert_eq!(value, json!({"tags": ["a", "b"]}));
+}
+
+#[test]
+fn decode_sniff_delimiter_recovers_tab_rows() {
+    let input = "users[2]{id,name,role}:\n  1\tAlice\tadmin\n  2,Bob,user";
+    let err = decode(input, &default_opts()).unwrap_err();
+    assert!(matches!(
+        err,
+        DecodeError::RowWidthMismatch {
+            line: 2,
+            expected: 3,
+            found: 1
+        }
+    ));
+
+    let opts = DecoderOptions {
+        sniff_delimiter: true,
+        ..default_opts()
+    };
+    let result = decode(input, &opts).unwrap();
+    assert_eq!(
+        result,
+        json!({"users": [
+            {"id": 1, "name": "Alice", "role": "admin"},
+            {"id": 2, "name": "Bob", "role": "user"}
+        ]})
+    );
+
+    // Rows that no delimiter fits are still rejected
+    let err = decode("users[1]{id,name}:\n  1\tAlice\tadmin", &opts).unwrap_err();
+    assert!(matches!(err, DecodeError::RowWidthMismatch { .. }));
+}
+
+#[test]
+fn parse_assignment_primitives_and_errors() {
+    use json2toon_rs::parse_assignment;
+
+    let cases = [
+        ("age: 30", "age", json!(30)),
+        ("  name: Alice Smith  ", "name", json!("Alice Smith")),
+        ("active: true", "active", json!(true)),
+        ("note: null", "note", json!(null)),
+        ("\"a:b\": \"x, y\"", "a:b", json!("x, y")),
+        ("[draft]: notes", "[draft]", json!("notes")),
+    ];
+    for (line, key, value) in cases {
+        assert_eq!(
+            parse_assignment(line, &default_opts()).unwrap(),
+            (key.to_string(), value),
+            "{}",
+            line
+        );
+    }
+
+    for line in ["user:", "tags[2]: a,b", "a: 1\nb: 2", ""] {
+        let err = parse_assignment(line, &default_opts()).unwrap_err();
+        assert!(
+            matches!(err, DecodeError::ParseError { .. }),
+            "{}: {:?}",
+            line,
+            err
+        );
+    }
+    for line in ["just text", "- a: 1"] {
+        let err = parse_assignment(line, &default_opts()).unwrap_err();
+        assert!(
+            matches!(err, DecodeError::InvalidLine { .. }),
+            "{}: {:?}",
+            line,
+            err
+        );
+    }
+    assert!(matches!(
+        parse_assignment("bad: \"\\q\"", &default_opts()),
+        Err(DecodeError::InvalidEscapeSequence { .. })
+    ));
+}
+
+#[test]
+fn decode_empty_string_keys_round_trip() {
+    let cases = [
+        (json!({"": 1}), "\"\": 1"),
+        (json!({"": [1, 2]}), "\"\"[2]: 1,2"),
+        (json!({"a": {"": {"": "x"}}}), "a:\n  \"\":\n    \"\": x"),
+        (json!({"": [{"": 1, "b": 2}]}), "\"\"[1]{\"\",b}:\n  1,2"),
+        (
+            json!([{"": 1}, {"": [1]}]),
+            "[2]:\n  - \"\": 1\n  - \"\"[1]: 1",
+        ),
+    ];
+    for (data, expected) in cases {
+        let toon = json2toon_rs::encode(&data, &json2toon_rs::EncoderOptions::default());
+        assert_eq!(toon, expected);
+        assert_eq!(decode(&toon, &default_opts()).unwrap(), data);
+    }
+}
+
+#[test]
+fn decode_list_item_array_fields() {
+    let input = "[1]:\n  - a[2]: 1,2\n  b[1]{x}:\n    3\n  c: 4";
+    assert_eq!(
+        decode(input, &default_opts()).unwrap(),
+        json!([{"a": [1, 2], "b": [{"x": 3}], "c": 4}])
+    );
+    let data = json!([{"a": 1, "b": [1, 2]}, {"c": [{"x": 1}, {"x": 2}]}]);
+    let toon = json2toon_rs::encode(&data, &json2toon_rs::EncoderOptions::default());
+    assert_eq!(
+        toon,
+        "[2]:\n  - a: 1\n  b[2]: 1,2\n  - c[2]{x}:\n    1\n    2"
+    );
+    assert_eq!(decode(&toon, &default_opts()).unwrap(), data);
+
+    // Older encoders put a colon between the key and the header
+    assert_eq!(
+        decode("[1]:\n  - a: 1\n  b:[2]: 1,2", &default_opts()).unwrap(),
+        json!([{"a": 1, "b": [1, 2]}])
+    );
+}
+
+#[test]
+fn decode_distinguishes_empty_object_and_null() {
+    let options = json2toon_rs::EncoderOptions::default();
+    let cases = [
+        (json!({"a": {}}), "a:"),
+        (json!({"a": null}), "a: null"),
+        (json!({"x": {"a": {}}, "y": null}), "x:\n  a:\ny: null"),
+        (json!([{"a": {}}, {"a": null}]), "[2]:\n  - a:\n  - a: null"),
+        (json!([{"b": 1, "a": {}}]), "[1]:\n  - b: 1\n  a:"),
+    ];
+    for (data, expected) in cases {
+        let toon = json2toon_rs::encode(&data, &options);
+        assert_eq!(toon, expected);
+        assert_eq!(decode(&toon, &default_opts()).unwrap(), data);
+        #[cfg(feature = "std")]
+        assert_eq!(
+            json2toon_rs::decode_reader(&mut toon.as_bytes(), &default_opts()).unwrap(),
+            data
+        );
+    }
+
+    // A bare key at the end of the input is still an empty object
+    for input in ["a:\n", "a:\n\n", "b: 1\na:   "] {
+        assert_eq!(decode(input, &default_opts()).unwrap()["a"], json!({}));
+    }
+}
+
+#[test]
+fn decode_extended_number_literals() {
+    let input = "mask: 0xFF\nperms: 0o755\nflags: -0b101\nlimit: 1_000\nrate: 1_000.5\n\
+                 big: 0xFFFF_FFFF_FFFF_FFFF\nmin: -0x8000000000000000\n\
+                 bad[5]: _1,1__0,0x,0xG,1_.5";
+    let opts = DecoderOptions {
+        extended_number_literals: true,
+        ..default_opts()
+    };
+    assert_eq!(
+        decode(input, &opts).unwrap(),
+        json!({
+            "mask": 255,
+            "perms": 493,
+            "flags": -5,
+            "limit": 1000,
+            "rate": 1000.5,
+            "big": u64::MAX,
+            "min": i64::MIN,
+            "bad": ["_1", "1__0", "0x", "0xG", "1_.5"]
+        })
+    );
+
+    // By default these forms are strings, as the spec requires
+    let result = decode("mask: 0xFF\nlimit: 1_000", &default_opts()).unwrap();
+    assert_eq!(result, json!({"mask": "0xFF", "limit": "1_000"}));
+}
+
+#[test]
+fn decode_inline_objects() {
+    let options = DecoderOptions {
+        inline_objects: true,
+        ..default_opts()
+    };
+    let input = "point: {x: 1, \"y z\": \"a, b\"}\nitems[1]:\n  - p: {k: true}\n  q: {n: null}";
+    assert_eq!(
+        decode(input, &options).unwrap(),
+        json!({
+            "point": {"x": 1, "y z": "a, b"},
+            "items": [{"p": {"k": true}, "q": {"n": null}}]
+        })
+    );
+
+    // Braced text that is not a list of entries stays a string
+    let input = "a: {draft}\nb: {}\nc: {x: 1, y}\nd: {x: {y: 1}}\ne: {x[1]: 2}";
+    assert_eq!(
+        decode(input, &options).unwrap(),
+        json!({"a": "{draft}", "b": "{}", "c": "{x: 1, y}", "d": "{x: {y: 1}}", "e": "{x[1]: 2}"})
+    );
+
+    let err = decode("p: {x: 1, x: 2}", &options).unwrap_err();
+    assert!(matches!(err, DecodeError::DuplicateKey { line: 1, .. }));
+
+    // By default braced text is a string
+    assert_eq!(
+        decode("note: {a: 1}", &default_opts()).unwrap(),
+        json!({"note": "{a: 1}"})
+    );
+}
+
+#[test]
+fn decode_extra_row_cells_lenient_are_reported() {
+    use json2toon_rs::{decode_with_warnings, Warning};
+
+    let input = "rows[2]{a,b,c}:\n  1,2,3,4\n  5,6,7";
+    assert!(matches!(
+        decode(input, &default_opts()),
+        Err(DecodeError::RowWidthMismatch {
+            line: 2,
+            expected: 3,
+            found: 4
+        })
+    ));
+
+    let (value, warnings) = decode_with_warnings(input, &DecoderOptions::lenient()).unwrap();
+    assert_eq!(
+        value,
+        json!({"rows": [{"a": 1, "b": 2, "c": 3}, {"a": 5, "b": 6, "c": 7}]})
+    );
+    assert_eq!(
+        warnings,
+        vec![Warning::RowWidthMismatch {
+            line: 2,
+            expected: 3,
+            found: 4
+        }]
+    );
+}
+
+#[test]
+fn decode_unicode_whitespace_indentation() {
+    // A no-break space (U+00A0) is two bytes and must not count as indentation
+    for input in ["a:\n\u{a0}b: 1", "a:\n  \u{a0}b: 1", "a:\n\u{3000}b: 1"] {
+        assert_eq!(
+            decode(input, &default_opts()),
+            Err(DecodeError::InvalidIndentation { line: 2 }),
+            "{:?}",
+            input
+        );
+    }
+
+    let tabs = DecoderOptions {
+        indent: IndentStyle::Tabs,
+        ..default_opts()
+    };
+    assert_eq!(
+        decode("a:\n\t\u{a0}b: 1", &tabs),
+        Err(DecodeError::InvalidIndentation { line: 2 })
+    );
+
+    // Lenient mode ignores it when measuring depth
+    let result = decode("a:\n  \u{a0}b: 1\n  c: 2", &DecoderOptions::lenient()).unwrap();
+    assert_eq!(result, json!({"a": {"b": 1, "c": 2}}));
+    let result = decode("a: 1\n\u{a0}b: 2", &DecoderOptions::lenient()).unwrap();
+    assert_eq!(result, json!({"a": 1, "b": 2}));
+}
+
+#[test]
+fn decode_unicode_whitespace_round_trip() {
+    let options = json2toon_rs::EncoderOptions::default();
+    for data in [
+        json!("\u{a0}x"),
+        json!({"a": "x\u{3000}", "b": ["\u{a0}", "y"]}),
+        json!({"rows": [{"id": 1, "v": "\u{2003}z"}, {"id": 2, "v": "w"}]}),
+    ] {
+        let toon = json2toon_rs::encode(&data, &options);
+        assert_eq!(decode(&toon, &default_opts()).unwrap(), data, "{}", toon);
+    }
+    assert_eq!(
+        json2toon_rs::encode(&json!("\u{a0}x"), &options),
+        "\"\u{a0}x\""
+    );
+}
+
+#[test]
+fn decode_infer_inline_arrays() {
+    let input = "tags: a,b,c\nmeta:\n  ids: 1, 2\nname: \"x,y\"\nplain: z";
+    let result = decode(input, &default_opts()).unwrap();
+    assert_eq!(
+        result,
+        json!({"tags": "a,b,c", "meta": {"ids": "1, 2"}, "name": "x,y", "plain": "z"})
+    );
+
+    let options = DecoderOptions {
+        infer_inline_arrays: true,
+        ..default_opts()
+    };
+    let result = decode(input, &options).unwrap();
+    assert_eq!(
+        result,
+        json!({"tags": ["a", "b", "c"], "meta": {"ids": [1, 2]}, "name": "x,y", "plain": "z"})
+    );
+
+    // List-item fields and quoted cells follow the same rules
+    let result = decode("items[1]:\n  - tags: \"a,b\",c", &options).unwrap();
+    assert_eq!(result, json!({"items": [{"tags": ["a,b", "c"]}]}));
+}
+
+#[test]
+fn detect_indent_width() {
+    assert_eq!(detect_indent("a:\n  b: 1\n  c:\n    d: 2"), Some(2));
+    assert_eq!(
+        detect_indent("a:\n    b:\n        c: 1\n\n    d: 2"),
+        Some(4)
+    );
+    assert_eq!(detect_indent("items[2]:\n   - 1\n   - 2"), Some(3));
+    assert_eq!(detect_indent("a:\n\tb:\n\t\tc: 1"), None);
+    assert_eq!(detect_indent("a: 1\nb: 2"), None);
+    assert_eq!(detect_indent(""), None);
+}
+
+#[test]
+fn decode_trailing_delimiter() {
+    let input = "tags[3]: a,b,c,";
+    assert_eq!(
+        decode(input, &default_opts()),
+        Err(DecodeError::ArrayLengthMismatch {
+            line: 1,
+            expected: 3,
+            found: 4
+        })
+    );
+
+    let options = DecoderOptions {
+        allow_trailing_delimiter: true,
+        ..default_opts()
+    };
+    assert_eq!(
+        decode(input, &options).unwrap(),
+        json!({"tags": ["a", "b", "c"]})
+    );
+    assert_eq!(
+        decode("tags[3|]: a|b|c| ", &options).unwrap(),
+        json!({"tags": ["a", "b", "c"]})
+    );
+    assert_eq!(
+        decode(input, &DecoderOptions::lenient()).unwrap(),
+        json!({"tags": ["a", "b", "c"]})
+    );
+
+    // A quoted empty last value is kept, and only one delimiter is dropped
+    assert_eq!(
+        decode("tags[3]: a,b,\"\"", &options).unwrap(),
+        json!({"tags": ["a", "b", ""]})
+    );
+    assert_eq!(
+        decode("tags[3]: a,b,,", &options).unwrap(),
+        json!({"tags": ["a", "b", ""]})
+    );
+}
+
+#[test]
+fn round_trip_diff_reports_first_divergence() {
+    use json2toon_rs::{round_trip_diff, EncoderOptions};
+
+    let encode_opts = EncoderOptions::default();
+    let value = json!({"name": "x", "items": [{"price": 1.5}, {"price": 1e19}]});
+    assert_eq!(
+        round_trip_diff(&value, &encode_opts, &default_opts()),
+        Ok(Some(
+            "/items/1/price: expected 1e+19, found 10000000000000000000".to_string()
+        ))
+    );
+
+    let value = json!({"a/b": {"c~": [1, "x"]}});
+    assert_eq!(
+        round_trip_diff(&value, &encode_opts, &default_opts()),
+        Ok(None)
+    );
+    let value = json!({"a/b": {"c~": [1, -0.0]}});
+    assert_eq!(
+        round_trip_diff(&value, &encode_opts, &default_opts()),
+        Ok(Some("/a~1b/c~0/1: expected -0.0, found 0".to_string()))
+    );
+    assert_eq!(
+        round_trip_diff(&json!(2.0), &encode_opts, &default_opts()),
+        Ok(Some("(root): expected 2.0, found 2".to_string()))
+    );
+}
+
+#[test]
+fn decode_escape_policies() {
+    use json2toon_rs::EscapePolicy;
+
+    let input = r#"s: "a\qb""#;
+    let with_policy = |policy| DecoderOptions {
+        escape_policy: Some(policy),
+        ..default_opts()
+    };
+
+    let err = decode(input, &with_policy(EscapePolicy::Strict)).unwrap_err();
+    assert!(matches!(
+        err,
+        DecodeError::InvalidEscapeSequence { line: 1, .. }
+    ));
+    assert_eq!(
+        decode(input, &with_policy(EscapePolicy::Preserve)).unwrap(),
+        json!({"s": "a\\qb"})
+    );
+    assert_eq!(
+        decode(input, &with_policy(EscapePolicy::Unescape)).unwrap(),
+        json!({"s": "aqb"})
+    );
+
+    // Without a policy, `strict` decides; a policy overrides it
+    assert!(decode(input, &default_opts()).is_err());
+    assert_eq!(
+        decode(input, &DecoderOptions::lenient()).unwrap(),
+        json!({"s": "a\\qb"})
+    );
+    let lenient_strict_escapes = DecoderOptions {
+        escape_policy: Some(EscapePolicy::Strict),
+        ..DecoderOptions::lenient()
+    };
+    assert!(decode(input, &lenient_strict_escapes).is_err());
+
+    // Known escapes are unaffected, also in keys and cells
+    let input = r#""k\é": "\té\x"
+rows[1]{a}:
+  "\z""#;
+    assert_eq!(
+        decode(input, &with_policy(EscapePolicy::Unescape)).unwrap(),
+        json!({"ké": "\téx", "rows": [{"a": "z"}]})
+    );
+}
+
+#[test]
+fn decode_auto_dedent() {
+    let input = "  name: Alice\n  age: 30\n  tags[2]: a,b\n  address:\n    city: Paris\n";
+    assert_eq!(
+        decode(input, &default_opts()),
+        Err(DecodeError::InvalidIndentation { line: 1 })
+    );
+
+    let options = DecoderOptions {
+        auto_dedent: true,
+        ..default_opts()
+    };
+    let expected = json!({
+        "name": "Alice",
+        "age": 30,
+        "tags": ["a", "b"],
+        "address": {"city": "Paris"}
+    });
+    assert_eq!(decode(input, &options).unwrap(), expected);
+    #[cfg(feature = "std")]
+    assert_eq!(
+        json2toon_rs::decode_reader(&mut input.as_bytes(), &options).unwrap(),
+        expected
+    );
+
+    // Blank lines and comments do not set the indentation
+    let commented = DecoderOptions {
+        allow_comments: true,
+        ..options.clone()
+    };
+    let input = "\n# copied\n    a:\n      b: 1\n\n    c: 2";
+    assert_eq!(
+        decode(input, &commented).unwrap(),
+        json!({"a": {"b": 1}, "c": 2})
+    );
+
+    // Spans still point into the original input
+    let input = "  a: 1\n  b: \"x\\qy\"";
+    let (_, span) = decode_with_span(input, &options).unwrap_err();
+    let span = span.unwrap();
+    assert_eq!(&input[span.start..span.end], "\\q");
+}